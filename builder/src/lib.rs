@@ -1,164 +1,604 @@
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
-use syn::{Attribute, Data, DeriveInput, Expr, Field, Fields, GenericArgument, Lit, Meta, MetaNameValue, parse_macro_input, PathArguments, Type, TypePath};
-use syn::punctuated::Iter;
+use syn::{Attribute, Data, DeriveInput, Expr, ExprClosure, ExprLit, Field, Fields, FieldsNamed, GenericArgument, GenericParam, Generics, Lit, LitStr, Meta, parse_macro_input, parse_quote, Pat, Path, PathArguments, Type, TypePath, Visibility, WherePredicate};
+use syn::meta::ParseNestedMeta;
 use syn::spanned::Spanned;
 
-fn only_type_path(f: &&Field) -> bool{
-    match &f.ty {
-        Type::Path(_) => true,
-        _ => false
+struct BuilderTarget<'a> {
+    builder_name: Ident,
+    builder_fn: Ident,
+    constructor: TokenStream,
+    fields: Vec<BuilderField<'a>>,
+}
+
+struct BuilderField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    attrs: FieldAttrs,
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    setter_prefix: Option<String>,
+    build_fn_name: Option<Ident>,
+    build_fn_vis: Option<Visibility>,
+    build_fn_error: Option<Type>,
+    post_build: Option<Path>,
+    default: bool,
+    parse_args: bool,
+    debug: bool,
+    required_ctor: bool,
+    const_fn: bool,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    each: Option<Ident>,
+    setter_name: Option<Ident>,
+    aliases: Vec<Ident>,
+    sensitive: bool,
+    doc: Option<String>,
+    setter_custom: bool,
+    setter_transform: Option<ExprClosure>,
+}
+
+fn convert<'a>(name: &Ident, data: &'a Data) -> syn::Result<Vec<BuilderTarget<'a>>> {
+    match data {
+        Data::Struct(data) => {
+            if let Fields::Named(ref fields) = data.fields {
+                return Ok(vec![BuilderTarget {
+                    builder_name: format_ident!("{}Builder", name),
+                    builder_fn: format_ident!("builder"),
+                    constructor: quote! { #name },
+                    fields: convert_fields(fields)?,
+                }]);
+            }
+            Err(syn::Error::new(name.span(), "expected a struct with named fields"))
+        }
+        // one builder per struct-like variant, unit and tuple variants are left alone
+        Data::Enum(data) => data.variants.iter().filter_map(|variant| {
+            let variant_name = &variant.ident;
+            if let Fields::Named(ref fields) = variant.fields {
+                return Some(convert_fields(fields).map(|fields| BuilderTarget {
+                    builder_name: format_ident!("{}{}Builder", name, variant_name),
+                    builder_fn: format_ident!("{}_builder", to_snake_case(&variant_name.to_string())),
+                    constructor: quote! { #name::#variant_name },
+                    fields,
+                }));
+            }
+            None
+        }).collect(),
+        Data::Union(data) => Err(syn::Error::new(data.union_token.span, "Builder is not supported for unions")),
     }
 }
 
-fn convert<F>(data: &Data, f: F)-> TokenStream where F: Fn(Iter<Field>) -> TokenStream {
-    if let Data::Struct(ref data) = *data {
-        if let Fields::Named(ref fields) = data.fields {
-            return f(fields.named.iter());
+fn convert_fields(fields: &FieldsNamed) -> syn::Result<Vec<BuilderField<'_>>> {
+    fields.named.iter().map(|f| {
+        Ok(BuilderField {
+            ident: f.ident.as_ref().unwrap(),
+            ty: &f.ty,
+            attrs: extract_field_attrs(f)?,
+        })
+    }).collect()
+}
+
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            // a run of capitals is one word, so `HTTPServer` becomes `http_server`
+            let starts_word = i > 0 && (!chars[i - 1].is_uppercase() || chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+            if starts_word {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
         }
     }
-    unimplemented!()
+    snake
 }
 
-fn build_function_macro(data: &Data) -> TokenStream {
-    convert(data, |fields| {
-        let items = fields.filter(only_type_path).map(|f| {
-            let name = &f.ident;
-            let ty = &f.ty;
-            if is_type(ty, "Option") || is_type(ty, "Vec"){
+fn build_function_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream {
+    let items = fields.iter().map(|f| {
+        let name = f.ident;
+        let ty = f.ty;
+        if struct_attrs.default {
+            // unset fields fall back to the struct's own Default impl
+            return if is_type(ty, "Option") {
                 quote! {
-                    #name: self.#name.clone()
+                    #name: self.#name.clone().or_else(|| default.#name.clone())
                 }
             } else {
                 quote! {
-                    #name: self.#name.clone().unwrap()
+                    #name: self.#name.clone().unwrap_or_else(|| default.#name.clone())
                 }
+            };
+        }
+        if is_type(ty, "Option") || is_type(ty, "Vec"){
+            quote! {
+                #name: self.#name.clone()
             }
-        });
-        quote! {
+        } else {
+            quote! {
+                #name: self.#name.clone().unwrap()
+            }
+        }
+    });
+    quote! {
+        #(#items),*
+    }
+}
+
+fn const_build_function_macro(fields: &[BuilderField]) -> TokenStream {
+    // `format!` is not usable in const fn, missing fields panic with a literal message
+    let items = fields.iter().map(|f| {
+        let name = f.ident;
+        if is_type(f.ty, "Option") {
+            quote! {
+                #name
+            }
+        } else {
+            quote! {
+                #name: std::option::Option::expect(#name, concat!(stringify!(#name), " missing"))
+            }
+        }
+    });
+    quote! {
+        #(#items),*
+    }
+}
+
+fn const_builder_macro(builder: TokenStream, fields: &[BuilderField], struct_attrs: &StructAttrs, required_ctor: bool) -> TokenStream {
+    // `Default::default` is not usable in const fn, so the builder is spelled out field by field
+    let items = fields.iter().map(|f| {
+        let name = f.ident;
+        if required_ctor && is_required(f, struct_attrs) {
+            quote! {
+                #name: std::option::Option::Some(#name)
+            }
+        } else {
+            quote! {
+                #name: std::option::Option::None
+            }
+        }
+    });
+    quote! {
+        #builder{
             #(#items),*
         }
-    })
+    }
 }
 
-fn build_check_macro(data: &Data) -> TokenStream{
-    convert(data, |fields| {
-        let items = fields.filter(only_type_path).
-            filter(|f|{
-                !is_type(&f.ty, "Option") && !is_type(&f.ty, "Vec")
-            })
-            .map(|f| {
+// under `builder(default)` a Vec field is kept in an Option, so that one explicitly set to be
+// empty is not mistaken for one that was never set
+fn is_tracked_vec(f: &BuilderField, struct_attrs: &StructAttrs) -> bool{
+    struct_attrs.default && is_type(f.ty, "Vec")
+}
 
-                let name = &f.ident;
-                quote! {
-                    if self.#name.is_none(){
-                        return std::result::Result::Err(format!("{} missing", stringify!(#name)).into());
-                    }
+fn is_required(f: &BuilderField, struct_attrs: &StructAttrs) -> bool{
+    !struct_attrs.default && !is_type(f.ty, "Option") && !is_type(f.ty, "Vec")
+}
+
+fn build_check_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let items = fields.iter().
+        filter(|f| is_required(f, struct_attrs))
+        .map(|f| {
+
+            let name = f.ident;
+            quote! {
+                if self.#name.is_none(){
+                    return std::result::Result::Err(format!("{} missing", stringify!(#name)).into());
                 }
-            });
-        quote! {
-            #(#items)*
+            }
+        });
+    quote! {
+        #(#items)*
+    }
+}
+
+fn required_ctor_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let required: Vec<&BuilderField> = fields.iter().filter(|f| is_required(f, struct_attrs)).collect();
+    let params = required.iter().map(|f| {
+        let name = f.ident;
+        let ty = f.ty;
+        quote! { #name: #ty }
+    });
+    let assigns = required.iter().map(|f| {
+        let name = f.ident;
+        quote! { builder.#name = std::option::Option::Some(#name); }
+    });
+    if struct_attrs.const_fn {
+        let builder = const_builder_macro(quote! { Self }, fields, struct_attrs, true);
+        return quote! {
+            pub const fn new(#(#params),*) -> Self{
+                #builder
+            }
+        };
+    }
+    let builder_mut = (!required.is_empty()).then(|| quote! { mut });
+    quote! {
+        pub fn new(#(#params),*) -> Self{
+            let #builder_mut builder = <Self as std::default::Default>::default();
+            #(#assigns)*
+            builder
         }
-    })
+    }
 }
 
-fn setter_function_macro(data: &Data) -> TokenStream{
-    convert(data, |fields|{
-        let items = fields.filter(only_type_path).map(|f| {
-            let name_ident = f.clone().ident.unwrap();
-            let name = name_ident.to_string();
-            let ty = &f.ty;
-            if is_type(ty, "Option"){
-                let param_ty = extract_generic_type(ty).unwrap();
-                return quote! {
-                    pub fn #name_ident(&mut self, #name_ident: #param_ty) -> &mut Self{
-                        self.#name_ident = std::option::Option::Some(#name_ident);
-                        self
-                    }
+fn assign_macro(struct_attrs: &StructAttrs, name: &Ident, value: TokenStream) -> TokenStream{
+    // a plain assignment drops the old value, which const fn cannot do for types like `String`,
+    // so a value that is set twice is leaked instead
+    if struct_attrs.const_fn {
+        quote! { std::mem::forget(std::mem::replace(&mut self.#name, #value)); }
+    } else {
+        quote! { self.#name = #value; }
+    }
+}
+
+fn setter_head(struct_attrs: &StructAttrs, setter: &Ident, param: &Ident, param_ty: &Type) -> TokenStream{
+    // const builders are consumed and returned by value, `&mut` is not usable in their callers
+    if struct_attrs.const_fn {
+        quote! { pub const fn #setter(mut self, #param: #param_ty) -> Self }
+    } else {
+        quote! { pub fn #setter(&mut self, #param: #param_ty) -> &mut Self }
+    }
+}
+
+fn extract_transform_input(transform: &ExprClosure) -> syn::Result<&Type> {
+    if transform.inputs.len() == 1 {
+        if let Pat::Type(pat_type) = &transform.inputs[0] {
+            return Ok(&pat_type.ty);
+        }
+    }
+    Err(syn::Error::new_spanned(&transform.inputs, "expected a closure with one typed parameter, like `|s: &str| ...`"))
+}
+
+// parameter type and assigned value of a setter, after any `setter(transform = ...)` or
+// smart pointer wrapping
+fn setter_input(f: &BuilderField, param_ty: &Type, struct_attrs: &StructAttrs) -> (Type, TokenStream) {
+    let name_ident = f.ident;
+    if let Some(transform) = &f.attrs.setter_transform {
+        return (extract_transform_input(transform).unwrap().clone(), quote! { (#transform)(#name_ident) });
+    }
+    match wrapped_smart_pointer(param_ty, struct_attrs) {
+        Some((pointer, _)) if pointer == "Cow" => {
+            (parse_quote!(impl std::convert::Into<#param_ty>), quote! { #name_ident.into() })
+        }
+        Some((_, inner)) => (inner.clone(), quote! { <#param_ty>::new(#name_ident) }),
+        None => (param_ty.clone(), quote! { #name_ident }),
+    }
+}
+
+// `Box<T>`, `Rc<T>`, `Arc<T>` and `Cow<'a, T>` setters take the pointee and wrap it
+fn wrapped_smart_pointer<'a>(ty: &'a Type, struct_attrs: &StructAttrs) -> Option<(&'a Ident, &'a Type)> {
+    // neither `new` nor `into` can be called in const fn
+    if struct_attrs.const_fn {
+        return None;
+    }
+    if let Type::Path(TypePath { qself: None, path }) = ty {
+        let segment = path.segments.last()?;
+        if !["Box", "Rc", "Arc", "Cow"].iter().any(|pointer| segment.ident == pointer) {
+            return None;
+        }
+        let inner = extract_generic_type(ty)?;
+        // an unsized pointee cannot be passed by value
+        if segment.ident != "Cow" && !is_sized(inner) {
+            return None;
+        }
+        return Some((&segment.ident, inner));
+    }
+    None
+}
+
+fn is_sized(ty: &Type) -> bool {
+    match ty {
+        Type::TraitObject(_) | Type::Slice(_) => false,
+        Type::Path(type_path) => !type_path.path.is_ident("str"),
+        _ => true,
+    }
+}
+
+fn raw_setter_macro(f: &BuilderField, struct_attrs: &StructAttrs, setter: &Ident, param_ty: &Type, value: TokenStream) -> TokenStream{
+    if f.attrs.setter_transform.is_some() || wrapped_smart_pointer(param_ty, struct_attrs).is_none() {
+        return quote! {};
+    }
+    let name_ident = f.ident;
+    let raw_setter = format_ident!("{}_raw", setter);
+    let head = setter_head(struct_attrs, &raw_setter, name_ident, param_ty);
+    let assign = assign_macro(struct_attrs, name_ident, value);
+    quote! {
+        #head{
+            #assign
+            self
+        }
+    }
+}
+
+fn setter_function_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let items = fields.iter().filter(|f| !f.attrs.setter_custom).map(|f| {
+        let name_ident = f.ident;
+        let setter_ident = setter_name(f, struct_attrs);
+        let ty = f.ty;
+        if is_type(ty, "Option"){
+            let inner_ty = extract_generic_type(ty).unwrap();
+            let (param_ty, value) = setter_input(f, inner_ty, struct_attrs);
+            let head = setter_head(struct_attrs, &setter_ident, name_ident, &param_ty);
+            let aliases = alias_function_macro(f, struct_attrs, &setter_ident, &param_ty);
+            let raw = raw_setter_macro(f, struct_attrs, &setter_ident, inner_ty, quote! {
+                std::option::Option::Some(#name_ident)
+            });
+            let assign = assign_macro(struct_attrs, name_ident, quote! { std::option::Option::Some(#value) });
+            return quote! {
+                #head{
+                    #assign
+                    self
                 }
+                #aliases
+                #raw
             }
-            if is_type(ty, "Vec"){
-                let builder_attr = extract_builder_attr(f);
-                if builder_attr.is_none(){
+        }
+        if is_type(ty, "Vec"){
+            let each_name_ident = match &f.attrs.each {
+                None => {
+                    let (param_ty, value) = setter_input(f, ty, struct_attrs);
+                    let head = setter_head(struct_attrs, &setter_ident, name_ident, &param_ty);
+                    let aliases = alias_function_macro(f, struct_attrs, &setter_ident, &param_ty);
+                    let value = if is_tracked_vec(f, struct_attrs) {
+                        quote! { std::option::Option::Some(#value) }
+                    } else {
+                        value
+                    };
                     return quote! {
-                        pub fn #name_ident(&mut self, #name_ident: #ty) -> &mut Self{
-                            self.#name_ident = #name_ident;
+                        #head{
+                            self.#name_ident = #value;
                             self
                         }
+                        #aliases
                     }
                 }
-                let builder_attr = builder_attr.unwrap();
-                let each_name = extract_each_name(builder_attr);
-                if let Err(ref err) = each_name{
-                    return err.to_compile_error();
+                Some(each_name_ident) => each_name_ident,
+            };
+            let param_ty = extract_generic_type(ty).unwrap();
+            let each_head = setter_head(struct_attrs, each_name_ident, each_name_ident, param_ty);
+            let (items, value) = if is_tracked_vec(f, struct_attrs) {
+                (quote! { self.#name_ident.get_or_insert_with(std::vec::Vec::new) }, quote! { std::option::Option::Some(#name_ident) })
+            } else {
+                (quote! { self.#name_ident }, quote! { #name_ident })
+            };
+            let each_set_fn = quote! {
+                #each_head{
+                    #items.push(#each_name_ident);
+                    self
                 }
-                let each_name = each_name.unwrap();
-                let each_name_ident = Ident::new(each_name.as_str(), f.span());
-                let param_ty = extract_generic_type(ty).unwrap();
-                let each_set_fn = quote! {
-                    pub fn #each_name_ident(&mut self, #each_name_ident: #param_ty) -> &mut Self{
-                        self.#name_ident.push(#each_name_ident);
+            };
+            // the one-at-a-time setter wins when both would get the same name
+            let set_fn = if *each_name_ident != setter_ident{
+                let head = setter_head(struct_attrs, &setter_ident, name_ident, ty);
+                let aliases = alias_function_macro(f, struct_attrs, &setter_ident, ty);
+                quote! {
+                    #head{
+                        self.#name_ident = #value;
                         self
                     }
-                };
-                let set_fn = if each_name.as_str() != name{
-                    quote! {
-                        pub fn #name_ident(&mut self, #name_ident: #ty) -> &mut Self{
-                            self.#name_ident = #name_ident;
-                            self
-                        }
-                    }
-                }else{
-                    quote! {}
-                };
-                return quote! {
-                    #set_fn
-                    #each_set_fn
+                    #aliases
                 }
-            }
-
+            }else{
+                alias_function_macro(f, struct_attrs, each_name_ident, param_ty)
+            };
             return quote! {
-                pub fn #name_ident(&mut self, #name_ident: #ty) -> &mut Self{
-                    self.#name_ident = std::option::Option::Some(#name_ident);
-                    self
-                }
+                #set_fn
+                #each_set_fn
             }
+        }
 
+        let (param_ty, value) = setter_input(f, ty, struct_attrs);
+        let head = setter_head(struct_attrs, &setter_ident, name_ident, &param_ty);
+        let aliases = alias_function_macro(f, struct_attrs, &setter_ident, &param_ty);
+        let raw = raw_setter_macro(f, struct_attrs, &setter_ident, ty, quote! {
+            std::option::Option::Some(#name_ident)
         });
+        let assign = assign_macro(struct_attrs, name_ident, quote! { std::option::Option::Some(#value) });
         quote! {
-            #(#items)*
+            #head{
+                #assign
+                self
+            }
+            #aliases
+            #raw
         }
-    })
+
+    });
+    quote! {
+        #(#items)*
+    }
 }
 
-fn builder_field_macro(data: &Data) -> TokenStream{
-    convert(data, |fields|{
-        let items = fields.filter(only_type_path).map(|f| {
-            let name = &f.ident;
-            let ty = &f.ty;
-            if is_type(ty, "Option") || is_type(ty, "Vec"){
-                quote! {
-                    #name: #ty
-                }
-            }else{
-                quote! {
-                    #name: std::option::Option<#ty>
-                }
+fn alias_function_macro(f: &BuilderField, struct_attrs: &StructAttrs, target: &Ident, param_ty: &Type) -> TokenStream{
+    let name_ident = f.ident;
+    let note = format!("use `{}` instead", target);
+    let items = f.attrs.aliases.iter().map(|alias| {
+        let head = setter_head(struct_attrs, alias, name_ident, param_ty);
+        quote! {
+            #[deprecated(note = #note)]
+            #head{
+                self.#target(#name_ident)
             }
-        });
+        }
+    });
+    quote! {
+        #(#items)*
+    }
+}
+
+fn setter_name(f: &BuilderField, struct_attrs: &StructAttrs) -> Ident{
+    if let Some(setter_name) = &f.attrs.setter_name {
+        return setter_name.clone();
+    }
+    match &struct_attrs.setter_prefix {
+        Some(prefix) => format_ident!("{}{}", prefix, f.ident),
+        None => f.ident.clone(),
+    }
+}
+
+fn debug_field_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let items = fields.iter().map(|f| {
+        let name = f.ident;
+        let (binding, value) = if f.attrs.sensitive {
+            (quote! { _ }, quote! { &format_args!("<redacted>") })
+        } else {
+            (quote! { #name }, quote! { #name })
+        };
+        if is_type(f.ty, "Vec") && !is_tracked_vec(f, struct_attrs) {
+            let value = if f.attrs.sensitive { value } else { quote! { &self.#name } };
+            return quote! {
+                debug.field(stringify!(#name), #value);
+            };
+        }
+        // unset optional fields are left out, unset required ones are called out
+        let unset = if is_type(f.ty, "Option") {
+            quote! {}
+        } else {
+            quote! { debug.field(stringify!(#name), &format_args!("<unset>")); }
+        };
         quote! {
-            #(#items),*
+            match &self.#name {
+                std::option::Option::Some(#binding) => { debug.field(stringify!(#name), #value); }
+                std::option::Option::None => { #unset }
+            }
         }
-    })
+    });
+    quote! {
+        #(#items)*
+    }
+}
+
+fn arg_flag(f: &BuilderField) -> String{
+    // repeated flags read better in the singular, so `each` fields use the element name
+    let name = f.attrs.each.as_ref().unwrap_or(f.ident);
+    name.to_string().trim_start_matches("r#").replace('_', "-")
+}
+
+fn parse_args_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let arms = fields.iter().map(|f| {
+        let name = f.ident;
+        let flag = arg_flag(f);
+        let parse = |ty: &Type| quote! {
+            value.parse::<#ty>().map_err(|err| format!("invalid value for `--{}`: {}", flag, err))?
+        };
+        let assign = if is_tracked_vec(f, struct_attrs) {
+            let value = parse(extract_generic_type(f.ty).unwrap());
+            quote! { builder.#name.get_or_insert_with(std::vec::Vec::new).push(#value); }
+        } else if is_type(f.ty, "Vec") {
+            let value = parse(extract_generic_type(f.ty).unwrap());
+            quote! { builder.#name.push(#value); }
+        } else if is_type(f.ty, "Option") {
+            let value = parse(extract_generic_type(f.ty).unwrap());
+            quote! { builder.#name = std::option::Option::Some(#value); }
+        } else {
+            let value = parse(f.ty);
+            quote! { builder.#name = std::option::Option::Some(#value); }
+        };
+        quote! {
+            #flag => { #assign }
+        }
+    });
+
+    let mut rows: Vec<(String, String)> = fields.iter().map(|f| {
+        let flag = arg_flag(f);
+        let mut usage = format!("--{} <{}>", flag, flag.to_uppercase());
+        if is_type(f.ty, "Vec") {
+            usage.push_str("...");
+        }
+        let mut description: Vec<&str> = f.attrs.doc.iter().map(String::as_str).collect();
+        if is_required(f, struct_attrs) {
+            description.push("(required)");
+        }
+        (usage, description.join(" "))
+    }).collect();
+    // a field of its own named `help` takes over the flag, the help text is still available
+    // through `parse_args_help`
+    let builtin_help = !fields.iter().any(|f| arg_flag(f) == "help");
+    if builtin_help {
+        rows.push(("--help".to_owned(), "Print this help text".to_owned()));
+    }
+    let help_check = builtin_help.then(|| quote! {
+        if arg == "--help" {
+            return std::result::Result::Err(Self::parse_args_help().into());
+        }
+    });
+    let width = rows.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    let mut help = String::from("Options:\n");
+    for (usage, description) in rows {
+        let line = format!("    {:width$}  {}", usage, description, width = width);
+        help.push_str(line.trim_end());
+        help.push('\n');
+    }
+
+    quote! {
+        pub fn parse_args_help() -> &'static str{
+            #help
+        }
+
+        pub fn parse_args<I>(args: I) -> std::result::Result<Self, std::boxed::Box<dyn std::error::Error>>
+        where
+            I: std::iter::IntoIterator,
+            I::Item: std::convert::Into<std::string::String>,
+        {
+            let mut builder = <Self as std::default::Default>::default();
+            let mut args = args.into_iter().map(std::convert::Into::into);
+            while let std::option::Option::Some(arg) = args.next() {
+                #help_check
+                let flag = match arg.strip_prefix("--") {
+                    std::option::Option::Some(flag) => flag,
+                    std::option::Option::None => return std::result::Result::Err(format!("unexpected argument `{}`", arg).into()),
+                };
+                let (flag, value) = match flag.split_once('=') {
+                    std::option::Option::Some((flag, value)) => (flag, value.to_owned()),
+                    std::option::Option::None => match args.next() {
+                        std::option::Option::Some(value) => (flag, value),
+                        std::option::Option::None => return std::result::Result::Err(format!("missing value for `--{}`", flag).into()),
+                    },
+                };
+                match flag {
+                    #(#arms)*
+                    _ => return std::result::Result::Err(format!("unrecognized argument `--{}`", flag).into()),
+                }
+            }
+            std::result::Result::Ok(builder)
+        }
+    }
+}
+
+fn builder_field_macro(fields: &[BuilderField], struct_attrs: &StructAttrs) -> TokenStream{
+    let items = fields.iter().map(|f| {
+        let name = f.ident;
+        let ty = f.ty;
+        if is_type(ty, "Option") || (is_type(ty, "Vec") && !is_tracked_vec(f, struct_attrs)){
+            quote! {
+                #name: #ty
+            }
+        }else{
+            quote! {
+                #name: std::option::Option<#ty>
+            }
+        }
+    });
+    quote! {
+        #(#items),*
+    }
 }
 
 fn extract_generic_type(ty: &Type) -> Option<&Type> {
     if let Type::Path(TypePath { qself: None, path }) = ty {
         if let Some(segment) = path.segments.last() {
             if let PathArguments::AngleBracketed(generic_args) = &segment.arguments {
-                if let Some(GenericArgument::Type(generic_type)) = generic_args.args.first() {
-                    return Some(generic_type);
-                }
+                // skip leading lifetimes, as in `Cow<'a, T>`
+                return generic_args.args.iter().find_map(|arg| match arg {
+                    GenericArgument::Type(generic_type) => Some(generic_type),
+                    _ => None,
+                });
             }
         }
     }
@@ -178,74 +618,342 @@ fn is_type(ty: &Type, ty_name: &'static str) -> bool{
     false
 }
 
-fn extract_builder_attr(f: &Field) -> Option<&Attribute>{
-    f.attrs.iter().find(|attr|{
+fn extract_builder_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute>{
+    attrs.iter().filter(|attr|{
         attr.path().is_ident("builder")
     })
 }
 
-fn extract_each_name(builder_attr: &Attribute) -> Result<String, syn::Error> {
-    if let Meta::List(meta_list) = &builder_attr.meta{
-        let kv = meta_list.parse_args::<MetaNameValue>().unwrap();
-        if !kv.path.is_ident("each"){
-            return Err(syn::Error::new(meta_list.span(), "expected `builder(each = \"...\")`"));
-        }
-        if let Expr::Lit(lit) = kv.value{
-            if let Lit::Str(str) = lit.lit{
-                return Ok(str.value());
+fn extract_ident_value(meta: &ParseNestedMeta) -> syn::Result<Ident> {
+    meta.value()?.parse::<LitStr>()?.parse()
+}
+
+fn extract_struct_attrs(attrs: &[Attribute]) -> syn::Result<StructAttrs> {
+    let mut struct_attrs = StructAttrs::default();
+    for attr in extract_builder_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("setter") {
+                return meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("prefix") {
+                        let prefix = meta.value()?.parse::<LitStr>()?;
+                        // the prefix is glued to field names, so it has to start an identifier
+                        if syn::parse_str::<Ident>(&format!("{}x", prefix.value())).is_err() {
+                            return Err(syn::Error::new(prefix.span(), "expected a setter prefix that can start an identifier, like `with_`"));
+                        }
+                        struct_attrs.setter_prefix = Some(prefix.value());
+                        return Ok(());
+                    }
+                    Err(meta.error("expected `setter(prefix = \"...\")`"))
+                });
+            }
+            if meta.path.is_ident("build_fn") {
+                return meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        struct_attrs.build_fn_name = Some(extract_ident_value(&meta)?);
+                    } else if meta.path.is_ident("vis") {
+                        struct_attrs.build_fn_vis = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                    } else if meta.path.is_ident("error") {
+                        struct_attrs.build_fn_error = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                    } else {
+                        return Err(meta.error("expected `build_fn(name = \"...\", vis = \"...\", error = \"...\")`"));
+                    }
+                    Ok(())
+                });
+            }
+            if meta.path.is_ident("default") {
+                struct_attrs.default = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("const") {
+                struct_attrs.const_fn = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("required_ctor") {
+                struct_attrs.required_ctor = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("parse_args") {
+                struct_attrs.parse_args = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("debug") {
+                struct_attrs.debug = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("post_build") {
+                struct_attrs.post_build = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            Err(meta.error("unrecognized builder attribute"))
+        })?;
+    }
+    Ok(struct_attrs)
+}
+
+fn extract_doc(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs.iter().filter_map(|attr| {
+        if let Meta::NameValue(name_value) = &attr.meta {
+            if name_value.path.is_ident("doc") {
+                if let Expr::Lit(ExprLit { lit: Lit::Str(str), .. }) = &name_value.value {
+                    return Some(str.value().trim().to_owned());
+                }
             }
         }
+        None
+    }).collect();
+    if lines.is_empty() {
+        return None;
     }
+    Some(lines.join(" "))
+}
 
-    return Err(syn::Error::new(builder_attr.span(), "expected `builder(each = \"...\")`"));
+fn extract_field_attrs(f: &Field) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs {
+        doc: extract_doc(&f.attrs),
+        ..FieldAttrs::default()
+    };
+    for attr in extract_builder_attrs(&f.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("each") {
+                field_attrs.each = Some(extract_ident_value(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("sensitive") {
+                field_attrs.sensitive = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("alias") {
+                field_attrs.aliases.push(extract_ident_value(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("setter") {
+                return meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        field_attrs.setter_name = Some(extract_ident_value(&meta)?);
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("custom") || meta.path.is_ident("skip") {
+                        field_attrs.setter_custom = true;
+                        return Ok(());
+                    }
+                    if meta.path.is_ident("transform") {
+                        field_attrs.setter_transform = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+                    Err(meta.error("expected `setter(name = \"...\")`, `setter(custom)` or `setter(transform = |...| ...)`"))
+                });
+            }
+            Err(syn::Error::new_spanned(&attr.meta, "expected `builder(each = \"...\")`"))
+        })?;
+    }
+    if field_attrs.each.is_some() && !is_type(&f.ty, "Vec") {
+        return Err(syn::Error::new(f.ty.span(), "`builder(each = \"...\")` requires a Vec field"));
+    }
+    if field_attrs.setter_custom && !field_attrs.aliases.is_empty() {
+        return Err(syn::Error::new_spanned(&field_attrs.aliases[0], "`alias` cannot forward to a `setter(custom)` setter"));
+    }
+    if let Some(transform) = &field_attrs.setter_transform {
+        if field_attrs.each.is_some() {
+            return Err(syn::Error::new_spanned(transform, "`setter(transform = ...)` cannot be combined with `each`"));
+        }
+        extract_transform_input(transform)?;
+    }
+    Ok(field_attrs)
 }
 
 
-#[proc_macro_derive(Builder, attributes(builder))]
-pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+// parsed values are assigned straight to the builder fields, so there is no input to hand to a
+// transform and no way to call a setter the user writes by hand
+fn check_parse_args(targets: &[BuilderTarget]) -> syn::Result<()> {
+    for f in targets.iter().flat_map(|target| &target.fields) {
+        if let Some(transform) = &f.attrs.setter_transform {
+            return Err(syn::Error::new_spanned(transform, "`setter(transform = ...)` is not supported by `builder(parse_args)`"));
+        }
+        if f.attrs.setter_custom {
+            return Err(syn::Error::new_spanned(f.ident, "`setter(custom)` is not supported by `builder(parse_args)`"));
+        }
+    }
+    Ok(())
+}
 
-    let name = input.ident;
-    let builder_name = format_ident!("{}Builder", name);
+fn check_const_builder(name: &Ident, targets: &[BuilderTarget], struct_attrs: &StructAttrs) -> syn::Result<()> {
+    if struct_attrs.default {
+        return Err(syn::Error::new(name.span(), "`builder(default)` is not supported by const builders"));
+    }
+    if let Some(post_build) = &struct_attrs.post_build {
+        return Err(syn::Error::new_spanned(post_build, "`builder(post_build = ...)` is not supported by const builders"));
+    }
+    if let Some(error) = &struct_attrs.build_fn_error {
+        return Err(syn::Error::new_spanned(error, "`build_fn(error = \"...\")` is not supported by const builders"));
+    }
+    for f in targets.iter().flat_map(|target| &target.fields) {
+        if is_type(f.ty, "Vec") {
+            return Err(syn::Error::new_spanned(f.ty, "Vec fields are not supported by const builders"));
+        }
+        if let Some(transform) = &f.attrs.setter_transform {
+            return Err(syn::Error::new_spanned(transform, "`setter(transform = ...)` is not supported by const builders"));
+        }
+    }
+    Ok(())
+}
+
+fn builder_macro(name: &Ident, generics: &Generics, target: &BuilderTarget, struct_attrs: &StructAttrs) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builder_name = &target.builder_name;
+    let constructor = &target.constructor;
+    let fields = &target.fields;
 
-    let fields = builder_field_macro(&input.data);
+    let builder_fields = builder_field_macro(fields, struct_attrs);
     let builder_def = quote! {
         #[derive(Default)]
-        pub struct #builder_name{
-            #fields
+        pub struct #builder_name #impl_generics #where_clause{
+            #builder_fields
         }
     };
 
-    let checks = build_check_macro(&input.data);
-    let assigns = build_function_macro(&input.data);
-    let build_fn = quote! {
-        pub fn build(&mut self) -> std::result::Result<#name, std::boxed::Box<dyn std::error::Error>>{
-            #checks
-            Ok(Command{
-                #assigns
-            })
+    let checks = build_check_macro(fields, struct_attrs);
+    let assigns = build_function_macro(fields, struct_attrs);
+    let default = struct_attrs.default.then(|| quote! {
+        let default = <#name #ty_generics as std::default::Default>::default();
+    });
+    let build_fn_name = struct_attrs.build_fn_name.clone().unwrap_or_else(|| format_ident!("build"));
+    let build_fn_vis = match &struct_attrs.build_fn_vis {
+        Some(vis) => quote! { #vis },
+        None => quote! { pub },
+    };
+    let error = match &struct_attrs.build_fn_error {
+        Some(error) => quote! { #error },
+        None => quote! { std::boxed::Box<dyn std::error::Error> },
+    };
+    // the hook may mutate the value or reject it with any error convertible into the build error
+    let post_build = struct_attrs.post_build.as_ref().map(|post_build| quote! {
+        #post_build(&mut value)?;
+    });
+    let value_mut = post_build.as_ref().map(|_| quote! { mut });
+    let build_fn = if struct_attrs.const_fn {
+        let assigns = const_build_function_macro(fields);
+        let field_names = fields.iter().map(|f| f.ident);
+        // const fn cannot drop the builder, even with its fields moved out, so they are taken
+        // and what is left of it is forgotten
+        quote! {
+            #build_fn_vis const fn #build_fn_name(mut self) -> #name #ty_generics{
+                #(let #field_names = std::option::Option::take(&mut self.#field_names);)*
+                std::mem::forget(self);
+                #constructor{
+                    #assigns
+                }
+            }
+        }
+    } else {
+        quote! {
+            #build_fn_vis fn #build_fn_name(&mut self) -> std::result::Result<#name #ty_generics, #error>{
+                #checks
+                #default
+                let #value_mut value = #constructor{
+                    #assigns
+                };
+                #post_build
+                std::result::Result::Ok(value)
+            }
         }
     };
-    let functions = setter_function_macro(&input.data);
-    let setter_fn = quote! {
-        impl #builder_name{
+    let functions = setter_function_macro(fields, struct_attrs);
+    // opt-in, since it needs every field that is not sensitive to be Debug
+    let debug_impl = struct_attrs.debug.then(|| {
+        let debug_fields = debug_field_macro(fields, struct_attrs);
+        quote! {
+            impl #impl_generics std::fmt::Debug for #builder_name #ty_generics #where_clause{
+                fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+                    let mut debug = fmt.debug_struct(stringify!(#builder_name));
+                    #debug_fields
+                    debug.finish()
+                }
+            }
+        }
+    });
+    let parse_args = struct_attrs.parse_args.then(|| parse_args_macro(fields, struct_attrs));
+    let required_ctor = struct_attrs.required_ctor.then(|| required_ctor_macro(fields, struct_attrs));
+    quote! {
+        #builder_def
+        #debug_impl
+        impl #impl_generics #builder_name #ty_generics #where_clause{
+            #required_ctor
             #functions
             #build_fn
+            #parse_args
         }
+    }
+}
+
+// the builder borrows for as long as the struct does, so it takes over the struct's lifetime
+// parameters and the bounds between them
+fn lifetime_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    generics.params = generics.params.into_iter().filter(|param| matches!(param, GenericParam::Lifetime(_))).collect();
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = where_clause.predicates.clone().into_iter().filter(|predicate| matches!(predicate, WherePredicate::Lifetime(_))).collect();
+    }
+    generics
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+
+    let struct_attrs = match extract_struct_attrs(&input.attrs) {
+        Ok(struct_attrs) => struct_attrs,
+        Err(err) => return err.to_compile_error().into(),
     };
+    if struct_attrs.default && !matches!(input.data, Data::Struct(_)) {
+        return syn::Error::new(name.span(), "`builder(default)` requires a struct")
+            .to_compile_error()
+            .into();
+    }
+    let targets = match convert(&name, &input.data) {
+        Ok(targets) => targets,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    if struct_attrs.const_fn {
+        if let Err(err) = check_const_builder(&name, &targets, &struct_attrs) {
+            return err.to_compile_error().into();
+        }
+    }
+
+    if struct_attrs.parse_args {
+        if let Err(err) = check_parse_args(&targets) {
+            return err.to_compile_error().into();
+        }
+    }
 
-    let builder_impl = quote! {
-        impl #name{
-            pub fn builder() -> #builder_name{
+    let generics = lifetime_generics(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let builders = targets.iter().map(|target| builder_macro(&name, &generics, target, &struct_attrs));
+    let builder_fns = targets.iter().map(|target| {
+        let builder_name = &target.builder_name;
+        let builder_fn = &target.builder_fn;
+        if struct_attrs.const_fn {
+            let builder = const_builder_macro(quote! { #builder_name }, &target.fields, &struct_attrs, false);
+            return quote! {
+                pub const fn #builder_fn() -> #builder_name #ty_generics{
+                    #builder
+                }
+            };
+        }
+        quote! {
+            pub fn #builder_fn() -> #builder_name #ty_generics{
                 Default::default()
             }
         }
-    };
+    });
 
     let tokens = quote! {
-        #builder_def
-        #setter_fn
-        #builder_impl
+        #(#builders)*
+        impl #impl_generics #name #ty_generics #where_clause{
+            #(#builder_fns)*
+        }
     };
 
     tokens.into()
@@ -0,0 +1,53 @@
+// Setter names do not always have to match field names.
+//
+// A struct-level #[builder(setter(prefix = "..."))] prepends a prefix to every
+// setter derived from a field name, a field-level
+// #[builder(setter(name = "..."))] picks the setter name outright, and
+// #[builder(alias = "...")] keeps an old setter name around as a deprecated
+// method forwarding to the current one. Together these let fields be renamed
+// without breaking callers and sidestep collisions with keywords or other
+// methods on the builder.
+//
+//     impl CommandBuilder {
+//         pub fn with_executable(&mut self, executable: String) -> &mut Self {...}
+//
+//         #[deprecated(note = "use `with_executable` instead")]
+//         pub fn program(&mut self, executable: String) -> &mut Self {
+//             self.with_executable(executable)
+//         }
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(setter(prefix = "with_"))]
+pub struct Command {
+    #[builder(alias = "program", alias = "exe")]
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(setter(name = "environment"))]
+    env: Vec<String>,
+    #[builder(setter(name = "kind"))]
+    r#type: Option<String>,
+}
+
+#[allow(deprecated)]
+fn main() {
+    let command = Command::builder()
+        .program("cargo".to_owned())
+        .arg("build".to_owned())
+        .with_args(vec!["build".to_owned(), "--release".to_owned()])
+        .environment(vec!["RUST_LOG=debug".to_owned()])
+        .kind("release".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.env, vec!["RUST_LOG=debug"]);
+    assert_eq!(command.r#type.as_deref(), Some("release"));
+
+    let command = Command::builder().exe("rustc".to_owned()).build().unwrap();
+    assert_eq!(command.executable, "rustc");
+}
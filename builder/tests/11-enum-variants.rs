@@ -0,0 +1,59 @@
+// Enums get one builder per struct-like variant.
+//
+// For a variant `Shape::Circle { .. }` generate a `ShapeCircleBuilder` reachable
+// through `Shape::circle_builder()`. Its setters follow the same Option, Vec
+// and #[builder(each = "...")] rules as a struct builder, and its `build`
+// method returns the enum itself. Unit and tuple variants have no fields to
+// set, so they get no builder. A run of capitals in a variant name counts as
+// one word, so `HTTPServer` is built through `http_server_builder()`.
+//
+//     impl Shape {
+//         pub fn circle_builder() -> ShapeCircleBuilder {...}
+//         pub fn polygon_builder() -> ShapePolygonBuilder {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: u32,
+        label: Option<String>,
+    },
+    Polygon {
+        #[builder(each = "point")]
+        points: Vec<(i32, i32)>,
+        filled: bool,
+    },
+    HTTPServer {
+        port: u16,
+    },
+    Empty,
+    Line(u32),
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(3).build().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 3, label: None });
+
+    let triangle = Shape::polygon_builder()
+        .point((0, 0))
+        .point((1, 0))
+        .point((0, 1))
+        .filled(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        triangle,
+        Shape::Polygon {
+            points: vec![(0, 0), (1, 0), (0, 1)],
+            filled: true,
+        },
+    );
+
+    assert!(Shape::polygon_builder().build().is_err());
+
+    let server = Shape::http_server_builder().port(80).build().unwrap();
+    assert_eq!(server, Shape::HTTPServer { port: 80 });
+    assert_ne!(Shape::Empty, Shape::Line(1));
+}
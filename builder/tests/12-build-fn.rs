@@ -0,0 +1,66 @@
+// The generated `build` method can be renamed, given a different visibility
+// and made to return a caller-supplied error type, which lets it sit behind a
+// handwritten public constructor.
+//
+//     #[builder(build_fn(name = "finish", vis = "pub(crate)", error = "MyError"))]
+//
+// Missing required fields are reported by converting a String into the error
+// type, so the error type must implement From<String>.
+//
+// A #[builder(post_build = path::to::fn)] hook runs on the constructed value
+// before it is returned. It takes `&mut T` and returns a Result whose error
+// converts into the build error, so it can normalize the value or reject it.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Missing(String),
+    Relative(String),
+}
+
+impl From<String> for ConfigError {
+    fn from(message: String) -> Self {
+        ConfigError::Missing(message)
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(name = "finish", vis = "pub(crate)", error = "ConfigError"))]
+#[builder(post_build = normalize)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+fn normalize(command: &mut Command) -> Result<(), ConfigError> {
+    if let Some(dir) = &mut command.current_dir {
+        if !dir.starts_with('/') {
+            return Err(ConfigError::Relative(dir.clone()));
+        }
+        while dir.len() > 1 && dir.ends_with('/') {
+            dir.pop();
+        }
+    }
+    Ok(())
+}
+
+impl Command {
+    pub fn new(executable: &str, current_dir: &str) -> Result<Command, ConfigError> {
+        Command::builder()
+            .executable(executable.to_owned())
+            .current_dir(current_dir.to_owned())
+            .finish()
+    }
+}
+
+fn main() {
+    let command = Command::new("cargo", "/home/user/").unwrap();
+    assert_eq!(command.current_dir.as_deref(), Some("/home/user"));
+
+    let err = Command::new("cargo", "src").unwrap_err();
+    assert_eq!(err, ConfigError::Relative("src".to_owned()));
+
+    let err = Command::builder().finish().unwrap_err();
+    assert_eq!(err, ConfigError::Missing("executable missing".to_owned()));
+}
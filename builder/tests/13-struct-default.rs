@@ -0,0 +1,78 @@
+// Large configuration structs often already implement Default. A struct-level
+// #[builder(default)] makes every field optional in the builder: `build`
+// computes the struct's Default value once and takes each field that was not
+// set from it.
+//
+// Option fields fall back to the default when no setter was called, and Vec
+// fields fall back to the default when they were neither set nor added to. A
+// Vec that was explicitly set to be empty stays empty.
+//
+// Structs that implement Drop cannot have fields moved out of them, so the
+// fallback clones each field out of the default value instead.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(default)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+    jobs: u32,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command {
+            executable: "cargo".to_owned(),
+            args: vec!["build".to_owned()],
+            env: vec![],
+            current_dir: Some("/".to_owned()),
+            jobs: 4,
+        }
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(default)]
+pub struct Connection {
+    host: String,
+    port: u16,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Connection {
+            host: "localhost".to_owned(),
+            port: 5432,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {}
+}
+
+fn main() {
+    let command = Command::builder().build().unwrap();
+    assert_eq!(command, Command::default());
+
+    let command = Command::builder()
+        .arg("test".to_owned())
+        .jobs(1)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["test"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+    assert_eq!(command.jobs, 1);
+
+    let command = Command::builder().args(vec![]).build().unwrap();
+    assert!(command.args.is_empty());
+
+    let connection = Connection::builder().port(6432).build().unwrap();
+    assert_eq!(connection.host, "localhost");
+    assert_eq!(connection.port, 6432);
+}
@@ -0,0 +1,73 @@
+// With #[builder(debug)] the builder implements Debug so a half-built value
+// can be logged, for example while reporting why `build` failed.
+//
+// Set fields print their value, required fields that have not been set print
+// as <unset>, and optional fields that have not been set are left out. Fields
+// marked #[builder(sensitive)] print as <redacted> once set, and their type
+// does not need to implement Debug.
+//
+// Without the attribute no Debug impl is generated, so fields whose type does
+// not implement Debug are fine, and the builder can implement Debug by hand.
+
+use derive_builder::Builder;
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Token(String);
+
+#[derive(Builder)]
+#[builder(debug)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    user: Option<String>,
+    #[builder(sensitive)]
+    token: Token,
+    #[builder(sensitive)]
+    password: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct NoDebug;
+
+#[derive(Builder)]
+pub struct Hook {
+    marker: NoDebug,
+    callback: Arc<dyn Fn()>,
+}
+
+impl Debug for HookBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("HookBuilder { .. }")
+    }
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.arg("build".to_owned()).current_dir("..".to_owned());
+    assert_eq!(
+        format!("{:?}", builder),
+        r#"CommandBuilder { executable: <unset>, args: ["build"], current_dir: "..", token: <unset> }"#,
+    );
+
+    builder
+        .executable("cargo".to_owned())
+        .token(Token("secret".to_owned()))
+        .password("hunter2".to_owned());
+    assert_eq!(
+        format!("{:?}", builder),
+        r#"CommandBuilder { executable: "cargo", args: ["build"], current_dir: "..", token: <redacted>, password: <redacted> }"#,
+    );
+
+    let command = builder.build().unwrap();
+    assert_eq!(command.token.0, "secret");
+    assert!(command.user.is_none());
+
+    let mut builder = Hook::builder();
+    builder.marker(NoDebug).callback(Arc::new(|| {}));
+    assert_eq!(format!("{:?}", builder), "HookBuilder { .. }");
+    (builder.build().unwrap().callback)();
+}
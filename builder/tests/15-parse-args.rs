@@ -0,0 +1,107 @@
+// A struct-level #[builder(parse_args)] adds a command-line parser to the
+// builder.
+//
+//     impl CommandBuilder {
+//         pub fn parse_args_help() -> &'static str {...}
+//         pub fn parse_args<I>(args: I) -> Result<Self, Box<dyn Error>>
+//         where
+//             I: IntoIterator,
+//             I::Item: Into<String>,
+//         {...}
+//     }
+//
+// Every field maps to a `--field value` or `--field=value` flag, with
+// underscores in the field name turned into dashes. Values are parsed with
+// FromStr. Vec fields accept the flag repeatedly, named after the element for
+// fields with #[builder(each = "...")], and Option fields may be left out. The
+// help text is assembled from the field doc comments and returned as the error
+// when `--help` is passed, unless a field of the struct is itself named `help`
+// and takes over that flag.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(parse_args, debug)]
+pub struct Command {
+    /// Program to run.
+    executable: String,
+    /// Argument passed to the program,
+    /// may be repeated.
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    /// Working directory.
+    current_dir: Option<String>,
+    jobs: u32,
+}
+
+#[derive(Builder)]
+#[builder(parse_args)]
+pub struct Topic {
+    /// Topic to show help for.
+    help: Option<String>,
+}
+
+fn main() {
+    let command = CommandBuilder::parse_args([
+        "--executable",
+        "cargo",
+        "--arg",
+        "build",
+        "--arg=--release",
+        "--jobs=8",
+    ])
+    .unwrap()
+    .build()
+    .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.jobs, 8);
+
+    let command = CommandBuilder::parse_args(vec![
+        "--executable=rustc".to_owned(),
+        "--current-dir".to_owned(),
+        "/tmp".to_owned(),
+        "--env".to_owned(),
+        "A=1".to_owned(),
+        "--jobs".to_owned(),
+        "1".to_owned(),
+    ])
+    .unwrap()
+    .build()
+    .unwrap();
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+    assert_eq!(command.env, vec!["A=1"]);
+
+    let help = "\
+Options:
+    --executable <EXECUTABLE>    Program to run. (required)
+    --arg <ARG>...               Argument passed to the program, may be repeated.
+    --env <ENV>...
+    --current-dir <CURRENT-DIR>  Working directory.
+    --jobs <JOBS>                (required)
+    --help                       Print this help text
+";
+    assert_eq!(CommandBuilder::parse_args_help(), help);
+    let err = CommandBuilder::parse_args(["--help"]).unwrap_err();
+    assert_eq!(err.to_string(), help);
+
+    let err = CommandBuilder::parse_args(["--jobs", "many"]).unwrap_err();
+    assert_eq!(err.to_string(), "invalid value for `--jobs`: invalid digit found in string");
+    let err = CommandBuilder::parse_args(["--verbose"]).unwrap_err();
+    assert_eq!(err.to_string(), "missing value for `--verbose`");
+    let err = CommandBuilder::parse_args(["--verbose=1"]).unwrap_err();
+    assert_eq!(err.to_string(), "unrecognized argument `--verbose`");
+    let err = CommandBuilder::parse_args(["cargo"]).unwrap_err();
+    assert_eq!(err.to_string(), "unexpected argument `cargo`");
+
+    let topic = TopicBuilder::parse_args(["--help", "builder"]).unwrap().build().unwrap();
+    assert_eq!(topic.help.as_deref(), Some("builder"));
+    assert_eq!(
+        TopicBuilder::parse_args_help(),
+        "Options:\n    --help <HELP>  Topic to show help for.\n",
+    );
+}
@@ -0,0 +1,50 @@
+// A struct-level #[builder(required_ctor)] generates a `new` constructor on
+// the builder taking every required field positionally, in declaration order.
+// Option and Vec fields stay with their setters. This makes the required set
+// obvious at the call site and turns a forgotten field into a compile error
+// instead of a failed `build`.
+//
+//     impl CommandBuilder {
+//         pub fn new(executable: String, current_dir: String) -> Self {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(required_ctor)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Option<Vec<String>>,
+    current_dir: String,
+}
+
+#[derive(Builder)]
+#[builder(required_ctor, default)]
+pub struct Limits {
+    jobs: u32,
+    timeout: Option<u64>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { jobs: 4, timeout: None }
+    }
+}
+
+fn main() {
+    let command = CommandBuilder::new("cargo".to_owned(), "..".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env, None);
+    assert_eq!(command.current_dir, "..");
+
+    let limits = LimitsBuilder::new().timeout(30).build().unwrap();
+    assert_eq!(limits.jobs, 4);
+    assert_eq!(limits.timeout, Some(30));
+}
@@ -0,0 +1,66 @@
+// A struct-level #[builder(const)] generates a builder that can run at compile
+// time, so static tables of configuration can be written with it directly.
+//
+//     static CFG: Config = Config::builder().name("a").retries(1).build();
+//
+// `builder`, the setters and `build` are all `const fn`. Since `&mut` receivers
+// cannot be chained in a static initializer, the setters take and return the
+// builder by value. Missing required fields make `build` panic, which is a
+// compile error when it happens during const evaluation. Field types have to
+// be usable in const context, so Vec fields are rejected. Fields of types with
+// a destructor, like String, are supported, and can be filled in at compile
+// time with values like String::new().
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const, required_ctor)]
+pub struct Config {
+    name: &'static str,
+    #[builder(alias = "attempts")]
+    retries: u32,
+    port: Option<u16>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const, build_fn(name = "finish"))]
+pub enum Endpoint {
+    Tcp { host: &'static str, port: u16 },
+}
+
+static CFG: Config = Config::builder().name("primary").retries(3).build();
+
+const TABLE: [Config; 2] = [
+    Config::builder().name("a").retries(1).port(80).build(),
+    ConfigBuilder::new("b", 2).build(),
+];
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Owned {
+    name: String,
+    alias: Option<String>,
+}
+
+const EMPTY: Owned = Owned::builder().name(String::new()).name(String::new()).build();
+
+const LOCAL: Endpoint = Endpoint::tcp_builder().host("localhost").port(8080).finish();
+
+#[allow(deprecated)]
+fn main() {
+    assert_eq!(CFG, Config { name: "primary", retries: 3, port: None });
+    assert_eq!(TABLE[0].port, Some(80));
+    assert_eq!(TABLE[1], Config { name: "b", retries: 2, port: None });
+    assert_eq!(LOCAL, Endpoint::Tcp { host: "localhost", port: 8080 });
+
+    let config = Config::builder().name("runtime").attempts(5).build();
+    assert_eq!(config.retries, 5);
+
+    assert_eq!(EMPTY, Owned { name: String::new(), alias: None });
+    let owned = Owned::builder()
+        .name("first".to_owned())
+        .alias("a".to_owned())
+        .name("second".to_owned())
+        .build();
+    assert_eq!(owned, Owned { name: "second".to_owned(), alias: Some("a".to_owned()) });
+}
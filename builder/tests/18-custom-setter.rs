@@ -0,0 +1,59 @@
+// Some setters need to normalize their input before storing it.
+//
+// #[builder(setter(custom))] keeps the field's slot in the builder and its
+// `build` handling but does not generate a setter, so the caller can write
+// their own in a separate impl block on the builder. Inside that impl block
+// the slot of a required or Option field is an Option to fill in, and the
+// slot of a Vec field is the Vec itself.
+//
+//     impl CommandBuilder {
+//         pub fn executable(&mut self, executable: &str) -> &mut Self {
+//             self.executable = Some(executable.trim().to_owned());
+//             self
+//         }
+//     }
+//
+// For simple conversions #[builder(setter(transform = |s: &str| ...))]
+// generates a setter taking the closure's parameter type and stores the
+// closure's result.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(custom))]
+    executable: String,
+    #[builder(setter(custom))]
+    args: Vec<String>,
+    #[builder(setter(transform = |s: &str| s.to_lowercase()))]
+    profile: String,
+    #[builder(setter(transform = |dir: &str| dir.trim_end_matches('/').to_owned()))]
+    current_dir: Option<String>,
+}
+
+impl CommandBuilder {
+    pub fn executable(&mut self, executable: &str) -> &mut Self {
+        self.executable = Some(executable.trim().to_owned());
+        self
+    }
+
+    pub fn args(&mut self, args: &str) -> &mut Self {
+        self.args = args.split_whitespace().map(str::to_owned).collect();
+        self
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("  cargo ")
+        .args("build --release")
+        .profile("RELEASE")
+        .current_dir("/home/user/")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.profile, "release");
+    assert_eq!(command.current_dir.as_deref(), Some("/home/user"));
+}
@@ -0,0 +1,89 @@
+// Fields holding a Box<T>, Rc<T> or Arc<T> get a setter that takes the T and
+// wraps it, and fields holding a Cow<'a, T> get a setter taking anything that
+// converts into the Cow. The same applies to such a pointer inside an Option.
+//
+// A second setter with a `_raw` suffix takes the already wrapped value, for
+// callers that want to share an existing Rc or Arc.
+//
+//     impl CommandBuilder {
+//         pub fn config(&mut self, config: Config) -> &mut Self {
+//             self.config = Some(Arc::new(config));
+//             self
+//         }
+//
+//         pub fn config_raw(&mut self, config: Arc<Config>) -> &mut Self {...}
+//     }
+//
+// Unsized pointees such as `Box<[u8]>` or `Arc<str>` cannot be passed by
+// value, so those fields only get the ordinary setter.
+//
+// A struct that borrows, like one with a `Cow<'a, str>` field, gets a builder
+// with the same lifetime parameters, `RequestBuilder<'a>`.
+
+use derive_builder::Builder;
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    jobs: u32,
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: Box<String>,
+    config: Arc<Config>,
+    parent: Option<Rc<Config>>,
+    profile: Cow<'static, str>,
+    output: Box<[u8]>,
+    name: Arc<str>,
+}
+
+#[derive(Builder)]
+pub struct Request<'a, 'b: 'a> {
+    path: Cow<'a, str>,
+    body: Option<&'b [u8]>,
+}
+
+fn main() {
+    let shared = Arc::new(Config { jobs: 4 });
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .config_raw(Arc::clone(&shared))
+        .parent(Config { jobs: 1 })
+        .profile("release")
+        .output(vec![1, 2].into_boxed_slice())
+        .name(Arc::from("build"))
+        .build()
+        .unwrap();
+
+    assert_eq!(*command.executable, "cargo");
+    assert!(Arc::ptr_eq(&command.config, &shared));
+    assert_eq!(command.parent.as_deref(), Some(&Config { jobs: 1 }));
+    assert!(matches!(command.profile, Cow::Borrowed("release")));
+    assert_eq!(&*command.output, [1, 2]);
+    assert_eq!(&*command.name, "build");
+
+    let command = Command::builder()
+        .executable_raw(Box::new("rustc".to_owned()))
+        .config(Config { jobs: 2 })
+        .parent_raw(Rc::new(Config { jobs: 3 }))
+        .profile(String::from("dev"))
+        .output(Box::new([]))
+        .name(Arc::from("check"))
+        .build()
+        .unwrap();
+
+    assert_eq!(*command.executable, "rustc");
+    assert_eq!(command.config.jobs, 2);
+    assert_eq!(command.parent.unwrap().jobs, 3);
+    assert_eq!(command.profile, "dev");
+
+    let body = vec![1, 2, 3];
+    let path = String::from("/index.html");
+    let request = Request::builder().path(path.as_str()).body(&body).build().unwrap();
+    assert!(matches!(request.path, Cow::Borrowed("/index.html")));
+    assert_eq!(request.body, Some(&[1, 2, 3][..]));
+}
@@ -0,0 +1,13 @@
+// A setter prefix is glued to the front of field names, so it has to be
+// something an identifier can start with. Anything else is reported at the
+// prefix rather than producing setters whose names do not parse.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(setter(prefix = "with-"))]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
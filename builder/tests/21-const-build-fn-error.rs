@@ -0,0 +1,15 @@
+// A const `build` panics on missing fields instead of returning a Result, so
+// there is no error type for `build_fn(error = "...")` to replace. Asking for
+// one on a const builder is reported at the error type.
+
+use derive_builder::Builder;
+
+pub struct ConfigError;
+
+#[derive(Builder)]
+#[builder(const, build_fn(error = "ConfigError"))]
+pub struct Config {
+    name: &'static str,
+}
+
+fn main() {}
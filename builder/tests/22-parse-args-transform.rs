@@ -0,0 +1,15 @@
+// #[builder(parse_args)] stores each parsed flag value directly in the
+// builder, bypassing the setters. A field whose setter transforms its input,
+// or is written by hand with setter(custom), would silently skip that logic,
+// so such fields are rejected on a struct that parses arguments.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(parse_args)]
+pub struct Command {
+    #[builder(setter(transform = |s: &str| s.to_lowercase()))]
+    executable: String,
+}
+
+fn main() {}
@@ -10,4 +10,17 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-setter-naming.rs");
+    t.pass("tests/11-enum-variants.rs");
+    t.pass("tests/12-build-fn.rs");
+    t.pass("tests/13-struct-default.rs");
+    t.pass("tests/14-builder-debug.rs");
+    t.pass("tests/15-parse-args.rs");
+    t.pass("tests/16-required-ctor.rs");
+    t.pass("tests/17-const-builder.rs");
+    t.pass("tests/18-custom-setter.rs");
+    t.pass("tests/19-smart-pointers.rs");
+    t.compile_fail("tests/20-invalid-setter-prefix.rs");
+    t.compile_fail("tests/21-const-build-fn-error.rs");
+    t.compile_fail("tests/22-parse-args-transform.rs");
 }
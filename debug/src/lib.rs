@@ -1,20 +1,149 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Attribute, Data, DeriveInput, Expr, ExprLit, Field, Fields, GenericArgument, GenericParam, Generics, Lit, Meta, MetaNameValue, parse_macro_input, parse_str, PathArguments, Type, TypePath, WhereClause, WherePredicate};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{Attribute, Data, DataEnum, DeriveInput, Expr, ExprLit, Field, Fields, GenericArgument, GenericParam, Generics, Index, Lit, LitInt, LitStr, Meta, parse_macro_input, parse_quote, Path, PathArguments, ReturnType, Token, Type, TypeParamBound, TypePath, TypeTraitObject, WhereClause, WherePredicate};
+use syn::parse::ParseStream;
 use syn::spanned::Spanned;
+use syn::punctuated::Punctuated;
+
+#[derive(Default)]
+struct FieldAttrs {
+    format: Option<LitStr>,
+    // explicit arguments of `#[debug("...", args...)]`, `None` for `#[debug = "..."]`
+    format_args: Option<Punctuated<Expr, Token![,]>>,
+    skip: bool,
+    redact: Option<String>,
+    with: Option<Path>,
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    rename: Option<LitStr>,
+    flatten: bool,
+    // number of items printed from a collection
+    limit: Option<usize>,
+    // number of characters printed from a string
+    max_len: Option<usize>,
+    // `{:#x}`, `{:#b}` or `{:#o}` for integers, applied element-wise
+    radix: Option<(Ident, &'static str)>,
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    // single-line output even under `{:#?}`
+    compact: bool,
+    // forward to the only field of a newtype
+    transparent: bool,
+    // default `limit` for every field that is a collection
+    limit: Option<usize>,
+    // can be the target of `debug(flatten)` in other structs
+    flattenable: bool,
+}
+
+impl FieldAttrs {
+    // fields whose own Debug impl is never called need no bound
+    fn uses_debug(&self) -> bool {
+        !self.skip && self.redact.is_none() && self.with.is_none() && self.radix.is_none()
+    }
+
+    // fields that format themselves some other way keep doing so
+    fn with_default_limit(mut self, ty: &Type, default_limit: Option<usize>) -> Self {
+        let formatted = self.format.is_some() || self.with.is_some() || self.redact.is_some() || self.radix.is_some() || self.max_len.is_some();
+        if self.limit.is_none() && !formatted && is_collection(ty) {
+            self.limit = default_limit;
+        }
+        self
+    }
+}
 
 #[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let struct_ident = input.ident;
-    let fields = match &input.data {
-        Data::Struct(data_struct) => &data_struct.fields,
-        _ => unreachable!()
+    match expand_debug(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_display(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+fn expand_debug(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let struct_ident = &input.ident;
+    let container_attrs = extract_container_attrs(&input.attrs)?;
+    let (body, fields): (TokenStream, Vec<&Field>) = match &input.data {
+        Data::Struct(data_struct) if container_attrs.transparent => {
+            (transparent_debug_macro(struct_ident, &data_struct.fields, container_attrs.limit)?, data_struct.fields.iter().collect())
+        }
+        Data::Struct(data_struct) => {
+            (struct_debug_macro(struct_ident, &data_struct.fields, container_attrs.limit)?, data_struct.fields.iter().collect())
+        }
+        Data::Enum(_) if container_attrs.transparent => {
+            return Err(syn::Error::new(struct_ident.span(), "`debug(transparent)` requires a struct with exactly one field"));
+        }
+        Data::Enum(data_enum) => {
+            (enum_debug_macro(struct_ident, data_enum, container_attrs.limit)?, data_enum.variants.iter().flat_map(|variant| &variant.fields).collect())
+        }
+        Data::Union(data_union) => {
+            return Err(syn::Error::new(data_union.union_token.span, "CustomDebug does not support unions"));
+        }
     };
-    let fields_debug = field_debug_macro(fields);
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let container_bounds = container_attrs.bound;
+    let type_param_set = get_all_generic_type_param_name_set(&input.generics);
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+    for field in fields {
+        let field_attrs = extract_field_attrs(field)?;
+        // a field-level bound replaces inference for that field only
+        if let Some(bounds) = field_attrs.bound {
+            predicates.extend(bounds);
+            continue;
+        }
+        // a container-level bound disables all inference of bounds
+        if container_bounds.is_some() || !field_attrs.uses_debug() {
+            continue;
+        }
+        for ty in get_name_of_type_or_associate_type_to_bound(&field.ty, &type_param_set){
+            predicates.push(parse_quote!(#ty: ::core::fmt::Debug));
+        }
+    }
+    predicates.extend(container_bounds.into_iter().flatten());
+    let new_where_clause = extend_where_clause(where_clause, predicates);
+
+    // a formatter without the alternate flag calls back into this impl for the single-line form
+    let compact = if container_attrs.compact {
+        quote! {
+            if fmt.alternate() {
+                return ::core::write!(fmt, "{:?}", self);
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let flatten_fields = if container_attrs.flattenable {
+        flatten_fields_macro(input, &new_where_clause, container_attrs.limit)?
+    } else {
+        quote! {}
+    };
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #struct_ident #ty_generics #new_where_clause{
+            fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result{
+                #compact
+                #body
+            }
+        }
+
+        #flatten_fields
+    })
+}
+
+fn extend_where_clause(where_clause: Option<&WhereClause>, predicates: Vec<WherePredicate>) -> WhereClause{
     let mut new_where_clause = if let Some(where_clause) = where_clause{
         where_clause.clone()
     }else{
@@ -23,70 +152,586 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             predicates: Default::default(),
         }
     };
-    let mut type_param_to_bound_set: HashSet<String> = HashSet::new();
-    match get_type_param_to_bound_from_attr(&input.attrs){
-        Err(err) => return err.into_compile_error().into(),
-        Ok(Some(bounds)) => {
-            // disable all inference of bounds
-            bounds.into_iter().for_each(|bound| { type_param_to_bound_set.insert(bound); });
+    let mut seen_predicates: HashSet<String> = HashSet::new();
+    for predicate in predicates {
+        if seen_predicates.insert(quote!(#predicate).to_string()) {
+            new_where_clause.predicates.push(predicate);
         }
-        _=>{
-            let type_param_set = get_all_generic_type_param_name_set(&input.generics);
-            for field in fields {
-                if let Some(expr) = get_name_of_type_or_associate_type_to_bound(&field.ty, &type_param_set){
-                    type_param_to_bound_set.insert(format!("{}: std::fmt::Debug", expr));
-                }
+    }
+    new_where_clause
+}
+
+fn struct_debug_macro(struct_ident: &Ident, fields: &Fields, default_limit: Option<usize>) -> syn::Result<TokenStream>{
+    let accessors: Vec<TokenStream> = fields.iter().enumerate().map(|(i, f)| {
+        match &f.ident {
+            Some(field_ident) => quote! { self.#field_ident },
+            None => {
+                let index = Index::from(i);
+                quote! { self.#index }
             }
         }
-    }
+    }).collect();
+    Ok(match fields {
+        Fields::Named(_) => field_debug_macro(quote! { fmt.debug_struct(stringify!(#struct_ident)) }, fields, &accessors, default_limit)?,
+        Fields::Unnamed(_) => field_debug_macro(quote! { fmt.debug_tuple(stringify!(#struct_ident)) }, fields, &accessors, default_limit)?,
+        Fields::Unit => quote! {
+            fmt.write_str(stringify!(#struct_ident))
+        },
+    })
+}
 
-    for string in type_param_to_bound_set {
-        let debug_bound: WherePredicate  = parse_str(&string).expect("Failed to parse where predicate");
-        new_where_clause.predicates.push(debug_bound);
+fn transparent_debug_macro(struct_ident: &Ident, fields: &Fields, default_limit: Option<usize>) -> syn::Result<TokenStream>{
+    let field = match fields.iter().collect::<Vec<_>>().as_slice() {
+        [field] => *field,
+        _ => return Err(syn::Error::new(struct_ident.span(), "`debug(transparent)` requires a struct with exactly one field")),
+    };
+    let field_attrs = extract_field_attrs(field)?.with_default_limit(&field.ty, default_limit);
+    if field_attrs.skip {
+        return Err(syn::Error::new_spanned(field, "the field of a `debug(transparent)` struct cannot be skipped"));
     }
+    let access = match &field.ident {
+        Some(field_ident) => quote! { self.#field_ident },
+        None => quote! { self.0 },
+    };
+    let argument = field_argument_macro(&field_attrs, &field.ty, &access, &HashMap::new())?;
+    Ok(quote! {
+        ::core::fmt::Debug::fmt(&#argument, fmt)
+    })
+}
 
-    let tokens = quote! {
-        impl #impl_generics std::fmt::Debug for #struct_ident #ty_generics #new_where_clause{
-            fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
-                fmt.debug_struct(stringify!(#struct_ident)).#fields_debug.finish()
+fn enum_debug_macro(enum_ident: &Ident, data_enum: &DataEnum, default_limit: Option<usize>) -> syn::Result<TokenStream>{
+    let arms = data_enum.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let bindings: Vec<Ident> = variant.fields.iter().enumerate().map(|(i, f)| {
+            match &f.ident {
+                Some(field_ident) => format_ident!("__field_{}", field_ident),
+                None => format_ident!("__field_{}", i),
             }
+        }).collect();
+        // bindings are references into `self`, deref them so they read like `self.field`
+        let accessors: Vec<TokenStream> = bindings.iter().map(|binding| quote! { (*#binding) }).collect();
+        Ok(match &variant.fields {
+            Fields::Named(fields) => {
+                let field_idents = fields.named.iter().map(|f| &f.ident);
+                let fields_debug = field_debug_macro(quote! { fmt.debug_struct(stringify!(#variant_ident)) }, &variant.fields, &accessors, default_limit)?;
+                quote! {
+                    #enum_ident::#variant_ident { #(#field_idents: #bindings),* } => #fields_debug
+                }
+            }
+            Fields::Unnamed(_) => {
+                let fields_debug = field_debug_macro(quote! { fmt.debug_tuple(stringify!(#variant_ident)) }, &variant.fields, &accessors, default_limit)?;
+                quote! {
+                    #enum_ident::#variant_ident(#(#bindings),*) => #fields_debug
+                }
+            }
+            Fields::Unit => quote! {
+                #enum_ident::#variant_ident => fmt.write_str(stringify!(#variant_ident)),
+            },
+        })
+    }).collect::<syn::Result<Vec<TokenStream>>>()?;
+    if data_enum.variants.is_empty() {
+        return Ok(quote! {
+            match *self {}
+        });
+    }
+    Ok(quote! {
+        match self {
+            #(#arms)*
         }
+    })
+}
+
+fn field_debug_macro(builder: TokenStream, fields: &Fields, accessors: &[TokenStream], default_limit: Option<usize>) -> syn::Result<TokenStream>{
+    let (statements, skipped, flattened) = field_statements_macro(fields, accessors, default_limit)?;
+    // let readers see that something was left out, which flattened fields only know at runtime
+    if flattened {
+        return Ok(quote! {
+            {
+                let debug_builder = &mut #builder;
+                let mut non_exhaustive = #skipped;
+                #(#statements)*
+                if non_exhaustive {
+                    debug_builder.finish_non_exhaustive()
+                } else {
+                    debug_builder.finish()
+                }
+            }
+        });
+    }
+    let finish = if skipped {
+        quote! { finish_non_exhaustive }
+    } else {
+        quote! { finish }
     };
+    Ok(quote! {
+        {
+            let debug_builder = &mut #builder;
+            #(#statements)*
+            debug_builder.#finish()
+        }
+    })
+}
 
-    tokens.into()
+// one statement per field writing into the `debug_builder` reference, plus whether any field was skipped and
+// whether any was flattened, in which case the statements also update `non_exhaustive`
+fn field_statements_macro(fields: &Fields, accessors: &[TokenStream], default_limit: Option<usize>) -> syn::Result<(Vec<TokenStream>, bool, bool)>{
+    let mut statements = Vec::new();
+    let mut skipped = false;
+    let mut flattened = false;
+    let siblings: HashMap<String, &TokenStream> = fields.iter().zip(accessors).filter_map(|(f, access)| {
+        f.ident.as_ref().map(|field_ident| (field_ident.to_string(), access))
+    }).collect();
+    for (f, access) in fields.iter().zip(accessors) {
+        let field_attrs = extract_field_attrs(f)?.with_default_limit(&f.ty, default_limit);
+        if field_attrs.skip {
+            skipped = true;
+            continue;
+        }
+        if field_attrs.flatten {
+            if f.ident.is_none() {
+                return Err(syn::Error::new_spanned(f, "`debug(flatten)` requires a named field"));
+            }
+            flattened = true;
+            // spanned so that a type without `debug(flattenable)` is reported at the field type
+            let ty = &f.ty;
+            statements.push(quote_spanned! {ty.span()=>
+                non_exhaustive |= <#ty>::__custom_debug_fields(&#access, debug_builder);
+            });
+            continue;
+        }
+        let argument = field_argument_macro(&field_attrs, &f.ty, access, &siblings)?;
+        statements.push(match (&f.ident, &field_attrs.rename) {
+            (Some(_), Some(rename)) => quote! {
+                debug_builder.field(#rename, &#argument);
+            },
+            (Some(field_ident), None) => quote! {
+                debug_builder.field(stringify!(#field_ident), &#argument);
+            },
+            (None, Some(rename)) => return Err(syn::Error::new_spanned(rename, "`debug(rename = \"...\")` requires a named field")),
+            (None, None) => quote! {
+                debug_builder.field(&#argument);
+            },
+        });
+    }
+    Ok((statements, skipped, flattened))
 }
 
-fn field_debug_macro(fields: &Fields) -> TokenStream{
-    let items = fields.iter().map(|f| {
+// fields of a `debug(flattenable)` struct, for parents that flatten it into their own output; an
+// inherent method rather than a trait since a proc-macro crate cannot export one, and only on
+// request so that other types keep their inherent namespace to themselves
+fn flatten_fields_macro(input: &DeriveInput, where_clause: &WhereClause, default_limit: Option<usize>) -> syn::Result<TokenStream>{
+    let fields = match &input.data {
+        Data::Struct(data_struct) if matches!(data_struct.fields, Fields::Named(_)) => &data_struct.fields,
+        _ => return Err(syn::Error::new(input.ident.span(), "`debug(flattenable)` requires a struct with named fields")),
+    };
+    let accessors: Vec<TokenStream> = fields.iter().map(|f| {
         let field_ident = &f.ident;
-        let argument = if let Some(format) = extract_debug_format(f){
-            quote! { format_args!(#format, &self.#field_ident) }
-        }else{
-            quote! { &self.#field_ident }
-        };
+        quote! { self.#field_ident }
+    }).collect();
+    let (statements, skipped, flattened) = field_statements_macro(fields, &accessors, default_limit)?;
+    let body = if flattened {
+        quote! {
+            let mut non_exhaustive = #skipped;
+            #(#statements)*
+            non_exhaustive
+        }
+    } else {
+        quote! {
+            #(#statements)*
+            #skipped
+        }
+    };
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause{
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub fn __custom_debug_fields(&self, debug_builder: &mut ::core::fmt::DebugStruct<'_, '_>) -> bool{
+                #body
+            }
+        }
+    })
+}
+
+fn field_argument_macro(field_attrs: &FieldAttrs, ty: &Type, access: &TokenStream, siblings: &HashMap<String, &TokenStream>) -> syn::Result<TokenStream>{
+    Ok(if let Some(placeholder) = &field_attrs.redact {
+        quote! { format_args!("{}", #placeholder) }
+    }else if let Some(with) = &field_attrs.with{
+        // adapt `fn(&T, &mut Formatter) -> fmt::Result` into something with a Debug impl, the
+        // function goes first so that it decides `T` and the field can deref-coerce to it
+        quote! {
+            {
+                struct DebugWith<'a, T: ?Sized>(fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result, &'a T);
+                impl<T: ?Sized> ::core::fmt::Debug for DebugWith<'_, T> {
+                    fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        (self.0)(self.1, fmt)
+                    }
+                }
+                DebugWith(#with, &#access)
+            }
+        }
+    }else if let Some(format) = &field_attrs.format{
+        format_debug_macro(format, field_attrs.format_args.as_ref(), access, siblings)?
+    }else if let Some((_, radix)) = field_attrs.radix{
+        radix_debug_macro(radix, ty, access)
+    }else if let Some(limit) = field_attrs.limit{
+        // anything whose shared reference iterates, printed as a list of at most `limit` items;
+        // a field like `&'a [T]` is dereferenced first since `&&[T]` does not iterate
+        let mut derefs = TokenStream::new();
+        let mut referenced = ty;
+        while let Type::Reference(reference) = referenced {
+            derefs.extend(quote! { * });
+            referenced = &reference.elem;
+        }
+        quote! {
+            {
+                struct DebugLimit<'a, T: ?Sized>(&'a T, usize);
+                impl<'a, T: ?Sized> ::core::fmt::Debug for DebugLimit<'a, T>
+                where
+                    &'a T: ::core::iter::IntoIterator,
+                    <&'a T as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug,
+                {
+                    fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        let mut items = ::core::iter::IntoIterator::into_iter(self.0);
+                        let mut list = fmt.debug_list();
+                        list.entries(items.by_ref().take(self.1));
+                        let rest = items.count();
+                        if rest > 0 {
+                            list.entry(&format_args!("... ({} more)", rest));
+                        }
+                        list.finish()
+                    }
+                }
+                DebugLimit(&#derefs #access, #limit)
+            }
+        }
+    }else if let Some(max_len) = field_attrs.max_len{
+        // anything that derefs to str, quoted like a string and cut after `max_len` characters
         quote! {
-            field(stringify!(#field_ident), &#argument)
+            {
+                struct DebugTruncate<'a>(&'a str, usize);
+                impl ::core::fmt::Debug for DebugTruncate<'_> {
+                    fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self.0.char_indices().nth(self.1) {
+                            ::core::option::Option::Some((end, _)) => {
+                                let rest = self.0[end..].chars().count();
+                                ::core::fmt::Debug::fmt(&self.0[..end], fmt)?;
+                                ::core::write!(fmt, "... ({} more)", rest)
+                            }
+                            ::core::option::Option::None => ::core::fmt::Debug::fmt(self.0, fmt),
+                        }
+                    }
+                }
+                DebugTruncate(&#access, #max_len)
+            }
         }
-    });
+    }else{
+        quote! { &#access }
+    })
+}
 
+// integers printed with `format`, looking through Option, Vec, arrays, slices and references so
+// that `Option<u32>` prints as `Some(0x2a)`; Vec is not in core, so its impl names Vec the way
+// the field type does and is only emitted when the field type mentions it
+fn radix_debug_macro(format: &str, ty: &Type, access: &TokenStream) -> TokenStream{
+    let integers = quote! { u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize };
+    let integers = integers.into_iter();
+    let mut seen_paths: HashSet<String> = HashSet::new();
+    let vec_paths: Vec<Path> = get_vec_paths(ty).into_iter().filter(|path| seen_paths.insert(quote!(#path).to_string())).collect();
     quote! {
-        #(#items).*
+        {
+            trait Radix {
+                fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result;
+            }
+            #(
+                impl Radix for #integers {
+                    fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::core::write!(fmt, #format, self)
+                    }
+                }
+            )*
+            impl<T: Radix + ?Sized> Radix for &T {
+                fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (**self).fmt_radix(fmt)
+                }
+            }
+            impl<T: Radix> Radix for ::core::option::Option<T> {
+                fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    match self {
+                        ::core::option::Option::Some(value) => fmt.debug_tuple("Some").field(&DebugRadix(value)).finish(),
+                        ::core::option::Option::None => fmt.write_str("None"),
+                    }
+                }
+            }
+            impl<T: Radix> Radix for [T] {
+                fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    fmt.debug_list().entries(self.iter().map(DebugRadix)).finish()
+                }
+            }
+            impl<T: Radix, const N: usize> Radix for [T; N] {
+                fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    self[..].fmt_radix(fmt)
+                }
+            }
+            #(
+                impl<T: Radix> Radix for #vec_paths<T> {
+                    fn fmt_radix(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        self[..].fmt_radix(fmt)
+                    }
+                }
+            )*
+            struct DebugRadix<'a, T: ?Sized>(&'a T);
+            impl<T: Radix + ?Sized> ::core::fmt::Debug for DebugRadix<'_, T> {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    self.0.fmt_radix(fmt)
+                }
+            }
+            DebugRadix(&#access)
+        }
+    }
+}
+
+// paths like `Vec` or `alloc::vec::Vec` without their generic arguments, wherever they appear
+fn get_vec_paths(ty: &Type) -> Vec<Path>{
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            let mut paths: Vec<Path> = get_generic_type_args(path).into_iter().flat_map(get_vec_paths).collect();
+            if path.segments.last().is_some_and(|segment| segment.ident == "Vec") {
+                let mut vec_path = path.clone();
+                vec_path.segments.iter_mut().for_each(|segment| segment.arguments = PathArguments::None);
+                paths.push(vec_path);
+            }
+            paths
+        }
+        Type::Array(array) => get_vec_paths(&array.elem),
+        Type::Slice(slice) => get_vec_paths(&slice.elem),
+        Type::Reference(reference) => get_vec_paths(&reference.elem),
+        Type::Paren(paren) => get_vec_paths(&paren.elem),
+        _ => Vec::new(),
+    }
+}
+
+fn format_debug_macro(
+    format: &LitStr,
+    args: Option<&Punctuated<Expr, Token![,]>>,
+    access: &TokenStream,
+    siblings: &HashMap<String, &TokenStream>,
+) -> syn::Result<TokenStream>{
+    let explicit_names: HashSet<String> = args.into_iter().flatten().filter_map(|arg| {
+        if let Expr::Assign(assign) = arg {
+            if let Expr::Path(path) = &*assign.left {
+                return path.path.get_ident().map(Ident::to_string);
+            }
+        }
+        None
+    }).collect();
+
+    let placeholders = extract_format_placeholders(&format.value());
+    let parameters = format_parameter_names(&placeholders);
+    let mut positional = false;
+    let mut names = Vec::new();
+    let mut named_args = Vec::new();
+    for (name, range, _) in placeholders {
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            positional = true;
+            continue;
+        }
+        if explicit_names.contains(&name) || names.contains(&name) {
+            continue;
+        }
+        // resolve named placeholders against sibling fields rather than whatever is in scope
+        let sibling = match siblings.get(&name) {
+            Some(sibling) => sibling,
+            None => return Err(syn::Error::new(
+                literal_subspan(format, range),
+                format!("unknown field `{}` in debug format string", name),
+            )),
+        };
+        let name_ident = format_ident!("{}", name);
+        named_args.push(if parameters.contains(&name) {
+            quote! { #name_ident = #sibling }
+        } else {
+            quote! { #name_ident = &#sibling }
+        });
+        names.push(name);
     }
+
+    // `#[debug("{:?}")]` in the list form has no arguments either, and prints the field
+    let args = match args {
+        Some(args) if !args.is_empty() => quote! { #args },
+        _ if positional => quote! { &#access },
+        _ => quote! {},
+    };
+    Ok(quote! { format_args!(#format, #args #(, #named_args)*) })
 }
 
-fn extract_debug_format(field: &Field)-> Option<String> {
-    field.attrs.iter().find_map(|attr| {
-        if let Meta::NameValue(name_value) = &attr.meta {
-            if name_value.path.is_ident("debug") {
-                if let Expr::Lit(lit) = &name_value.value {
-                    if let Lit::Str(str) = &lit.lit {
-                        return Some(str.value());
+// argument names of every `{...}` placeholder and of the `name$` width and precision parameters
+// in their format spec, with their byte range in the string and whether they are a parameter
+fn extract_format_placeholders(format: &str) -> Vec<(String, Range<usize>, bool)> {
+    let mut placeholders = Vec::new();
+    let mut chars = format.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|&(_, c)| c == '{').is_some() => {}
+            '}' => {
+                chars.next_if(|&(_, c)| c == '}');
+            }
+            '{' => {
+                let start = i + 1;
+                let mut end = start;
+                for (j, c) in chars.by_ref() {
+                    if c == '}' {
+                        end = j;
+                        break;
+                    }
+                }
+                let spec = &format[start..end];
+                let name = spec.split(':').next().unwrap_or_default();
+                let name_start = start + name.len() - name.trim_start().len();
+                let trimmed = name.trim();
+                placeholders.push((trimmed.to_owned(), name_start..name_start + trimmed.len(), false));
+                // named width and precision parameters, like `width$` in `{:>width$}`
+                if let Some(options) = spec.get(name.len() + 1..) {
+                    let options_start = start + name.len() + 1;
+                    for (dollar, _) in options.match_indices('$') {
+                        let parameter = &options[..dollar];
+                        let parameter_len = parameter.len() - parameter.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_').len();
+                        if parameter_len > 0 {
+                            let parameter_start = options_start + dollar - parameter_len;
+                            placeholders.push((format[parameter_start..options_start + dollar].to_owned(), parameter_start..options_start + dollar, true));
+                        }
                     }
                 }
             }
+            _ => {}
         }
-        None
-    })
+    }
+    placeholders
+}
+
+// width and precision parameters have to be passed as a `usize` rather than a reference to one
+fn format_parameter_names(placeholders: &[(String, Range<usize>, bool)]) -> HashSet<String> {
+    placeholders.iter().filter(|(_, _, parameter)| *parameter).map(|(name, _, _)| name.clone()).collect()
+}
+
+// narrow a span down to part of a string literal, where the compiler supports it
+fn literal_subspan(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let value = lit.value();
+    if let Some(offset) = repr.find('"').map(|quote| quote + 1) {
+        // only possible when no escape sequence shifts the source text against the value
+        if repr.get(offset..offset + value.len()) == Some(value.as_str()) {
+            let range = offset + range.start..offset + range.end;
+            if let Some(span) = token.subspan(range) {
+                return span;
+            }
+        }
+    }
+    lit.span()
+}
+
+fn extract_field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        match &attr.meta {
+            Meta::NameValue(name_value) => {
+                if let Expr::Lit(ExprLit { lit: Lit::Str(str), .. }) = &name_value.value {
+                    field_attrs.format = Some(str.clone());
+                } else {
+                    return Err(syn::Error::new_spanned(&name_value.value, "expected `debug = \"...\"`"));
+                }
+            }
+            Meta::List(meta_list) if starts_with_literal(&meta_list.tokens) => {
+                let (format, args) = meta_list.parse_args_with(|input: ParseStream| {
+                    let format: LitStr = input.parse()?;
+                    let mut args = Punctuated::new();
+                    if !input.is_empty() {
+                        input.parse::<Token![,]>()?;
+                        args = Punctuated::parse_terminated(input)?;
+                    }
+                    Ok((format, args))
+                })?;
+                field_attrs.format = Some(format);
+                field_attrs.format_args = Some(args);
+            }
+            Meta::List(_) => attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("redact") {
+                    let placeholder = if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<LitStr>()?.value()
+                    } else {
+                        "<redacted>".to_owned()
+                    };
+                    field_attrs.redact = Some(placeholder);
+                    return Ok(());
+                }
+                if meta.path.is_ident("with") {
+                    field_attrs.with = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("rename") {
+                    field_attrs.rename = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("flatten") {
+                    field_attrs.flatten = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("limit") {
+                    field_attrs.limit = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("max_len") {
+                    field_attrs.max_len = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    return Ok(());
+                }
+                let radix = [("hex", "{:#x}"), ("binary", "{:#b}"), ("octal", "{:#o}")]
+                    .into_iter()
+                    .find(|(name, _)| meta.path.is_ident(name));
+                if let Some((_, format)) = radix {
+                    let ident = meta.path.require_ident()?.clone();
+                    if let Some((previous, _)) = &field_attrs.radix {
+                        return Err(meta.error(format!("`debug({})` cannot be combined with `debug({})`", ident, previous)));
+                    }
+                    field_attrs.radix = Some((ident, format));
+                    return Ok(());
+                }
+                if meta.path.is_ident("bound") {
+                    field_attrs.bound = Some(parse_bound(meta.value()?.parse()?)?);
+                    return Ok(());
+                }
+                Err(meta.error("unrecognized debug attribute"))
+            })?,
+            Meta::Path(path) => return Err(syn::Error::new_spanned(path, "expected `debug = \"...\"` or `debug(...)`")),
+        }
+    }
+    if let (Some(with), Some(_)) = (&field_attrs.with, &field_attrs.format) {
+        return Err(syn::Error::new_spanned(with, "`debug(with = ...)` cannot be combined with `debug = \"...\"`"));
+    }
+    if let (Some((radix, _)), true) = (&field_attrs.radix, field_attrs.format.is_some() || field_attrs.with.is_some() || field_attrs.redact.is_some()) {
+        return Err(syn::Error::new_spanned(radix, format!("`debug({})` cannot be combined with a custom format", radix)));
+    }
+    let formatted = field_attrs.format.is_some() || field_attrs.with.is_some() || field_attrs.redact.is_some() || field_attrs.radix.is_some();
+    let truncated = field_attrs.limit.is_some() || field_attrs.max_len.is_some();
+    if field_attrs.flatten && (formatted || truncated || field_attrs.rename.is_some()) {
+        return Err(syn::Error::new_spanned(field, "`debug(flatten)` cannot be combined with options that format the field itself"));
+    }
+    if truncated && formatted {
+        return Err(syn::Error::new_spanned(field, "`debug(limit = ...)` and `debug(max_len = ...)` cannot be combined with a custom format"));
+    }
+    if field_attrs.limit.is_some() && field_attrs.max_len.is_some() {
+        return Err(syn::Error::new_spanned(field, "`debug(limit = ...)` cannot be combined with `debug(max_len = ...)`"));
+    }
+    Ok(field_attrs)
+}
+
+fn starts_with_literal(tokens: &TokenStream) -> bool {
+    matches!(tokens.clone().into_iter().next(), Some(TokenTree::Literal(_)))
 }
 
 fn get_all_generic_type_param_name_set(generics: &Generics) -> HashSet<String> {
@@ -98,49 +743,283 @@ fn get_all_generic_type_param_name_set(generics: &Generics) -> HashSet<String> {
     }).collect()
 }
 
-fn get_name_of_type_or_associate_type_to_bound(ty: &Type, type_param_set: &HashSet<String>) -> Option<String>{
-    if let Type::Path(TypePath { qself: None, path }) = ty {
-        // for Foo<Bar<...<T>>>
-        if let Some(segment) = path.segments.last() {
-            let type_name = segment.ident.to_string();
-            if type_param_set.contains(type_name.as_str()){
-                return Some(type_name)
+fn get_name_of_type_or_associate_type_to_bound(ty: &Type, type_param_set: &HashSet<String>) -> Vec<Type>{
+    let recurse = |ty: &Type| get_name_of_type_or_associate_type_to_bound(ty, type_param_set);
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            if path.segments.len() == 1 && type_param_set.contains(path.segments[0].ident.to_string().as_str()){
+                return vec![ty.clone()];
+            }
+            if is_phantom_data(path){
+                return Vec::new();
+            }
+            // for T::Value and longer chains like T::A::B
+            if path.leading_colon.is_none() && path.segments.len() >= 2 && type_param_set.contains(path.segments[0].ident.to_string().as_str()){
+                return vec![ty.clone()];
+            }
+            // for Foo<Bar<...<T>>> and every argument of HashMap<K, V>
+            get_generic_type_args(path).into_iter().flat_map(recurse).collect()
+        }
+        // for <T as Trait>::Value, bounded as written whenever the qualified type involves a type param
+        Type::Path(TypePath { qself: Some(qself), .. }) => {
+            if recurse(&qself.ty).is_empty() {
+                return Vec::new();
             }
-            if type_name == "PhantomData"{
-                return None;
+            vec![ty.clone()]
+        }
+        Type::Tuple(tuple) => tuple.elems.iter().flat_map(recurse).collect(),
+        Type::Array(array) => recurse(&array.elem),
+        Type::Slice(slice) => recurse(&slice.elem),
+        Type::Reference(reference) => recurse(&reference.elem),
+        Type::Paren(paren) => recurse(&paren.elem),
+        Type::Group(group) => recurse(&group.elem),
+        // for Box<dyn Trait<T>>
+        Type::TraitObject(TypeTraitObject { bounds, .. }) => bounds.iter().flat_map(|bound| {
+            match bound {
+                TypeParamBound::Trait(trait_bound) => get_generic_type_args(&trait_bound.path),
+                _ => Vec::new(),
             }
-            if let PathArguments::AngleBracketed(generic_args) = &segment.arguments {
-                if let Some(GenericArgument::Type(generic_type)) = generic_args.args.first() {
-                    return get_name_of_type_or_associate_type_to_bound(generic_type, type_param_set);
+        }).flat_map(recurse).collect(),
+        // raw pointers and fn pointers are Debug whatever they point to
+        _ => Vec::new(),
+    }
+}
+
+// types a struct-level `debug(limit = ...)` applies to, recognized by how they are spelled
+fn is_collection(ty: &Type) -> bool{
+    match ty {
+        Type::Array(_) | Type::Slice(_) => true,
+        Type::Reference(reference) => is_collection(&reference.elem),
+        Type::Paren(paren) => is_collection(&paren.elem),
+        Type::Path(TypePath { qself: None, path }) => path.segments.last().is_some_and(|segment| {
+            ["Vec", "VecDeque", "LinkedList", "BinaryHeap", "HashMap", "HashSet", "BTreeMap", "BTreeSet"]
+                .iter()
+                .any(|name| segment.ident == name)
+        }),
+        _ => false,
+    }
+}
+
+fn is_phantom_data(path: &Path) -> bool{
+    let segments: Vec<String> = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+    match segments.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["PhantomData"] => path.leading_colon.is_none(),
+        ["marker", "PhantomData"] => path.leading_colon.is_none(),
+        ["std" | "core", "marker", "PhantomData"] => true,
+        _ => false,
+    }
+}
+
+fn get_generic_type_args(path: &Path) -> Vec<&Type>{
+    path.segments.iter().flat_map(|segment| {
+        match &segment.arguments {
+            PathArguments::AngleBracketed(generic_args) => generic_args.args.iter().filter_map(|arg| {
+                match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    GenericArgument::AssocType(assoc_type) => Some(&assoc_type.ty),
+                    _ => None,
                 }
+            }).collect(),
+            PathArguments::Parenthesized(fn_args) => {
+                let output = match &fn_args.output {
+                    ReturnType::Type(_, ty) => Some(&**ty),
+                    ReturnType::Default => None,
+                };
+                fn_args.inputs.iter().chain(output).collect()
             }
+            PathArguments::None => Vec::new(),
         }
-        if path.segments.len() == 2 && type_param_set.contains(path.segments[0].ident.to_string().as_str()){
-            return Some(path.segments.iter()
-                .map(|segment| segment.ident.to_string())
-                .collect::<Vec<_>>()
-                .join("::"))
+    }).collect()
+}
+
+fn extract_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs>{
+    let mut container_attrs = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        if !matches!(attr.meta, Meta::List(_)) {
+            return Err(syn::Error::new_spanned(&attr.meta, "expected `debug(...)`"));
         }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                container_attrs.bound = Some(parse_bound(meta.value()?.parse()?)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("compact") {
+                container_attrs.compact = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("transparent") {
+                container_attrs.transparent = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("flattenable") {
+                container_attrs.flattenable = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("limit") {
+                container_attrs.limit = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                return Ok(());
+            }
+            Err(meta.error("unrecognized debug attribute"))
+        })?;
     }
-    None
+
+    Ok(container_attrs)
 }
 
-fn get_type_param_to_bound_from_attr(attrs: &[Attribute]) -> syn::Result<Option<Vec<String>>>{
-    for attr in attrs {
-        if let Meta::List(meta_list) = &attr.meta {
-            let path = &meta_list.path;
-            if path.is_ident("debug") {
-                if let Ok(kv) = meta_list.parse_args::<MetaNameValue>() {
-                    if kv.path.is_ident("bound") {
-                        if let Expr::Lit( ExprLit { lit: Lit::Str(lit), ..}) = kv.value {
-                            return Ok(Some(lit.value().split(",").map(|s|s.to_owned()).collect()))
-                        }
+// parses `"T: Trait<A, B>, for<'a> F: Fn(&'a u8)"`, with errors spanned to the string literal
+fn parse_bound(lit: LitStr) -> syn::Result<Punctuated<WherePredicate, Token![,]>> {
+    lit.parse_with(Punctuated::parse_terminated)
+}
+
+fn expand_display(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (body, displayed_fields): (TokenStream, Vec<&Field>) = match &input.data {
+        Data::Struct(data_struct) => {
+            let accessors: Vec<TokenStream> = data_struct.fields.iter().enumerate().map(|(i, f)| {
+                match &f.ident {
+                    Some(field_ident) => quote! { self.#field_ident },
+                    None => {
+                        let index = Index::from(i);
+                        quote! { self.#index }
                     }
                 }
-                return Err(syn::Error::new(meta_list.span(), "expected `debug(bound = \"...\")`"));
+            }).collect();
+            match extract_display_template(&input.attrs)? {
+                Some(template) => display_template_macro(&template, &data_struct.fields, &accessors)?,
+                None if matches!(data_struct.fields, Fields::Unit) => (quote! { fmt.write_str(stringify!(#ident)) }, Vec::new()),
+                None => return Err(syn::Error::new(ident.span(), "expected `#[display(\"...\")]` on the struct")),
+            }
+        }
+        Data::Enum(data_enum) => {
+            if let Some(attr) = input.attrs.iter().find(|attr| attr.path().is_ident("display")) {
+                return Err(syn::Error::new_spanned(attr, "`#[display(\"...\")]` goes on each variant of an enum"));
+            }
+            enum_display_macro(ident, data_enum)?
+        }
+        Data::Union(data_union) => {
+            return Err(syn::Error::new(data_union.union_token.span, "CustomDisplay does not support unions"));
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // only the fields the templates mention need to be Display
+    let type_param_set = get_all_generic_type_param_name_set(&input.generics);
+    let predicates = displayed_fields.iter().flat_map(|field| {
+        get_name_of_type_or_associate_type_to_bound(&field.ty, &type_param_set)
+    }).map(|ty| parse_quote!(#ty: ::core::fmt::Display)).collect();
+    let new_where_clause = extend_where_clause(where_clause, predicates);
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #new_where_clause{
+            fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result{
+                #body
+            }
+        }
+    })
+}
+
+fn enum_display_macro<'a>(enum_ident: &Ident, data_enum: &'a DataEnum) -> syn::Result<(TokenStream, Vec<&'a Field>)>{
+    let mut displayed_fields = Vec::new();
+    let arms = data_enum.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let bindings: Vec<Ident> = variant.fields.iter().enumerate().map(|(i, f)| {
+            match &f.ident {
+                Some(field_ident) => format_ident!("__field_{}", field_ident),
+                None => format_ident!("__field_{}", i),
+            }
+        }).collect();
+        let accessors: Vec<TokenStream> = bindings.iter().map(|binding| quote! { (*#binding) }).collect();
+        let write = match extract_display_template(&variant.attrs)? {
+            Some(template) => {
+                let (write, fields) = display_template_macro(&template, &variant.fields, &accessors)?;
+                displayed_fields.extend(fields);
+                write
+            }
+            None if matches!(variant.fields, Fields::Unit) => quote! { fmt.write_str(stringify!(#variant_ident)) },
+            None => return Err(syn::Error::new(variant_ident.span(), "expected `#[display(\"...\")]` on the variant")),
+        };
+        // fields the template leaves out are still bound, silence them
+        Ok(match &variant.fields {
+            Fields::Named(fields) => {
+                let field_idents = fields.named.iter().map(|f| &f.ident);
+                quote! {
+                    #[allow(unused_variables)]
+                    #enum_ident::#variant_ident { #(#field_idents: #bindings),* } => #write,
+                }
             }
+            Fields::Unnamed(_) => quote! {
+                #[allow(unused_variables)]
+                #enum_ident::#variant_ident(#(#bindings),*) => #write,
+            },
+            Fields::Unit => quote! {
+                #enum_ident::#variant_ident => #write,
+            },
+        })
+    }).collect::<syn::Result<Vec<TokenStream>>>()?;
+    if data_enum.variants.is_empty() {
+        return Ok((quote! { match *self {} }, displayed_fields));
+    }
+    Ok((quote! {
+        match self {
+            #(#arms)*
+        }
+    }, displayed_fields))
+}
+
+fn extract_display_template(attrs: &[Attribute]) -> syn::Result<Option<LitStr>>{
+    let mut template = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("display")) {
+        match &attr.meta {
+            Meta::List(meta_list) => template = Some(meta_list.parse_args::<LitStr>()?),
+            meta => return Err(syn::Error::new_spanned(meta, "expected `display(\"...\")`")),
         }
     }
+    Ok(template)
+}
 
-    Ok(None)
+// `{name}` and `{0}` placeholders refer to fields, which format_args! has no syntax for, so each
+// one is renamed to `__field_{name}` in the template and passed in as a named argument
+fn display_template_macro<'a>(template: &LitStr, fields: &'a Fields, accessors: &[TokenStream]) -> syn::Result<(TokenStream, Vec<&'a Field>)>{
+    let value = template.value();
+    let mut rewritten = String::new();
+    let mut last = 0;
+    let mut named_args = Vec::new();
+    let mut displayed_fields = Vec::new();
+    let mut names = HashSet::new();
+    let placeholders = extract_format_placeholders(&value);
+    let parameters = format_parameter_names(&placeholders);
+    for (name, range, _) in placeholders {
+        let found = fields.iter().enumerate().find(|(i, f)| {
+            match &f.ident {
+                Some(field_ident) => *field_ident == name,
+                None => i.to_string() == name,
+            }
+        });
+        let (position, field) = match found {
+            Some(found) => found,
+            None if name.is_empty() => return Err(syn::Error::new(
+                template.span(),
+                "display template placeholders must name a field, like `{name}` or `{0}`",
+            )),
+            None => return Err(syn::Error::new(
+                literal_subspan(template, range),
+                format!("unknown field `{}` in display template", name),
+            )),
+        };
+        let arg_ident = format_ident!("__field_{}", name);
+        rewritten.push_str(&value[last..range.start]);
+        rewritten.push_str(&arg_ident.to_string());
+        last = range.end;
+        if names.insert(name.clone()) {
+            let access = &accessors[position];
+            named_args.push(if parameters.contains(&name) {
+                quote! { #arg_ident = #access }
+            } else {
+                quote! { #arg_ident = &#access }
+            });
+            displayed_fields.push(field);
+        }
+    }
+    rewritten.push_str(&value[last..]);
+    let rewritten = LitStr::new(&rewritten, template.span());
+    Ok((quote! { ::core::write!(fmt, #rewritten #(, #named_args)*) }, displayed_fields))
 }
@@ -0,0 +1,63 @@
+// Enums are supported too. Each variant prints the way the standard library's
+// derive would print it: unit variants as their name, tuple variants through
+// `debug_tuple` and struct variants through `debug_struct`.
+//
+// Field attributes such as #[debug = "..."] work on variant fields the same
+// way they work on struct fields, and bounds are inferred from the fields of
+// every variant unless a #[debug(bound = "...")] attribute on the enum
+// replaces them.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub enum Message<T> {
+    Quit,
+    Move {
+        x: i32,
+        #[debug = "{:+}"]
+        y: i32,
+    },
+    Write(String, #[debug = "0x{:02x}"] u8),
+    Payload(T),
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug")]
+pub enum Wrapper<T: Trait> {
+    Values(Vec<T::Value>),
+    Empty {},
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let quit: Message<()> = Message::Quit;
+    assert_eq!(format!("{:?}", quit), "Quit");
+
+    let moved: Message<()> = Message::Move { x: 1, y: 2 };
+    assert_eq!(format!("{:?}", moved), "Move { x: 1, y: +2 }");
+
+    let write: Message<()> = Message::Write("hi".to_owned(), 10);
+    assert_eq!(format!("{:?}", write), r#"Write("hi", 0x0a)"#);
+
+    assert_eq!(format!("{:?}", Message::Payload([1, 2])), "Payload([1, 2])");
+
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id>>();
+    assert_debug::<Never>();
+    assert_eq!(format!("{:?}", Wrapper::<Id>::Values(vec![7])), "Values([7])");
+    assert_eq!(format!("{:?}", Wrapper::<Id>::Empty {}), "Empty");
+}
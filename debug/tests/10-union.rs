@@ -0,0 +1,13 @@
+// Unions carry no record of which field is active, so there is no sensible
+// Debug output to generate for them. Report a compile error pointing at the
+// `union` keyword instead.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
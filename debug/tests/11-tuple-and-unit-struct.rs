@@ -0,0 +1,26 @@
+// Tuple structs have no field names to print, so they go through
+// `debug_tuple` and read their fields as `self.0`, `self.1`, ... instead. Unit
+// structs print as just their name. Both match the output of the standard
+// library's derive, including for #[debug = "..."] on tuple fields.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Rgb(u8, #[debug = "{:#04x}"] u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T>(pub T);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+#[derive(CustomDebug)]
+pub struct Empty();
+
+fn main() {
+    assert_eq!(format!("{:?}", Rgb(1, 255, 3)), "Rgb(1, 0xff, 3)");
+    assert_eq!(format!("{:?}", Wrapper("w")), r#"Wrapper("w")"#);
+    assert_eq!(format!("{:?}", Marker), "Marker");
+    assert_eq!(format!("{:?}", Empty()), "Empty");
+    assert_eq!(format!("{:#?}", Rgb(1, 2, 3)), "Rgb(\n    1,\n    0x02,\n    3,\n)");
+}
@@ -0,0 +1,64 @@
+// Structs holding secrets should be safe to log.
+//
+// #[debug(skip)] leaves a field out of the output entirely. To make the
+// omission visible the output ends with `..`, as produced by
+// `finish_non_exhaustive`. #[debug(redact)] keeps the field but prints
+// `<redacted>` in place of its value, and #[debug(redact = "...")] picks the
+// placeholder.
+//
+// Neither kind of field calls its type's Debug impl, so neither needs a Debug
+// bound on its type.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Login<T> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "***")]
+    token: T,
+    #[debug(skip)]
+    session: Session,
+}
+
+pub struct Session;
+
+#[derive(CustomDebug)]
+pub struct Key(u32, #[debug(skip)] Vec<u8>);
+
+#[derive(CustomDebug)]
+pub enum Credential {
+    Password(#[debug(redact)] String),
+    Token {
+        id: u32,
+        #[debug(skip)]
+        secret: String,
+    },
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Login<NotDebug>>();
+
+    let login = Login {
+        user: "admin",
+        password: "hunter2".to_owned(),
+        token: 42,
+        session: Session,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "admin", password: <redacted>, token: ***, .. }"#,
+    );
+
+    assert_eq!(format!("{:?}", Key(1, vec![2])), "Key(1, ..)");
+
+    let password = Credential::Password("hunter2".to_owned());
+    assert_eq!(format!("{:?}", password), "Password(<redacted>)");
+    let token = Credential::Token { id: 7, secret: "s3cr3t".to_owned() };
+    assert_eq!(format!("{:?}", token), "Token { id: 7, .. }");
+}
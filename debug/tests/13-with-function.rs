@@ -0,0 +1,66 @@
+// A format string applies a single `format_args!` to the field, which is not
+// enough for types that need real code to print, or that have no Debug impl
+// at all. #[debug(with = path::to::function)] hands the field to a function of
+// the shape
+//
+//     fn(&T, &mut fmt::Formatter) -> fmt::Result
+//
+// and uses whatever it writes as the field's output. The field only has to
+// coerce to the function's parameter, so a Vec<u8> or [u8; N] field can use a
+// function taking &[u8]. Since the field's own Debug impl is not used, its
+// type does not need a Debug bound.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod fmt_utils {
+    use std::fmt;
+
+    pub fn hex(bytes: &[u8], fmt: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(fmt, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn unix_seconds(time: &SystemTime, fmt: &mut fmt::Formatter) -> fmt::Result {
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+    write!(fmt, "@{}", seconds)
+}
+
+fn opaque<T>(_: &T, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.write_str("<opaque>")
+}
+
+#[derive(CustomDebug)]
+pub struct Record<T> {
+    #[debug(with = unix_seconds)]
+    created: SystemTime,
+    #[debug(with = fmt_utils::hex)]
+    digest: [u8; 4],
+    #[debug(with = opaque)]
+    handle: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Digest(#[debug(with = fmt_utils::hex)] Vec<u8>);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Record<NotDebug>>();
+
+    let record = Record {
+        created: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        digest: [0xde, 0xad, 0xbe, 0xef],
+        handle: NotDebug,
+    };
+    assert_eq!(
+        format!("{:?}", record),
+        "Record { created: @1700000000, digest: deadbeef, handle: <opaque> }",
+    );
+    assert_eq!(format!("{:?}", Digest(vec![1, 2])), "Digest(0102)");
+}
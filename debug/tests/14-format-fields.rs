@@ -0,0 +1,91 @@
+// A field's format string is not limited to the field itself.
+//
+// Named placeholders such as `{width}` refer to sibling fields of the same
+// struct or variant, so a field can be printed in terms of its neighbours.
+// This includes named width and precision parameters like `{:>width$}`.
+// The list form #[debug("...", args...)] takes explicit format arguments the
+// same way `format_args!` does, for example `self.num, self.den`, and its
+// named placeholders that have no explicit argument are again looked up among
+// the sibling fields. Explicit arguments are evaluated as written, so they can
+// only use `self.field` in structs; enum variants rely on named placeholders.
+// Without any explicit arguments, as in #[debug("{:?}")], positional
+// placeholders print the field just like the #[debug = "..."] form.
+//
+// Placeholder names are checked during macro expansion, see the next test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    #[debug = "{width}x{height} ({:?})"]
+    format: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Padded {
+    width: usize,
+    precision: usize,
+    #[debug = "[{:>width$}]"]
+    id: u32,
+    #[debug = "{:.precision$} ({precision})"]
+    score: f64,
+}
+
+#[derive(CustomDebug)]
+pub struct Ratio {
+    num: i32,
+    den: i32,
+    #[debug("{}/{} = {value:.2}", self.num, self.den, value = self.num as f64 / self.den as f64)]
+    display: (),
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    Rect {
+        w: u32,
+        h: u32,
+        #[debug("{w}*{h}={}", "?")]
+        area: (),
+    },
+    Circle {
+        #[debug("r={:?}")]
+        r: u32,
+    },
+}
+
+fn main() {
+    let image = Image {
+        width: 640,
+        height: 480,
+        format: "png",
+    };
+    assert_eq!(
+        format!("{:?}", image),
+        r#"Image { width: 640, height: 480, format: 640x480 ("png") }"#,
+    );
+
+    let padded = Padded {
+        width: 5,
+        precision: 1,
+        id: 42,
+        score: 0.25,
+    };
+    assert_eq!(
+        format!("{:?}", padded),
+        "Padded { width: 5, precision: 1, id: [   42], score: 0.2 (1) }",
+    );
+
+    let ratio = Ratio { num: 1, den: 3, display: () };
+    assert_eq!(
+        format!("{:?}", ratio),
+        "Ratio { num: 1, den: 3, display: 1/3 = 0.33 }",
+    );
+
+    let rect = Shape::Rect { w: 2, h: 3, area: () };
+    assert_eq!(format!("{:?}", rect), "Rect { w: 2, h: 3, area: 2*3=? }");
+
+    let circle = Shape::Circle { r: 4 };
+    assert_eq!(format!("{:?}", circle), "Circle { r: r=4 }");
+}
@@ -0,0 +1,23 @@
+// A named placeholder that matches none of the sibling fields is reported
+// during macro expansion, pointing into the string literal where the compiler
+// allows it, rather than silently capturing some unrelated variable in scope.
+// The same goes for named width and precision parameters.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    #[debug = "{width}x{heigth}"]
+    size: (),
+}
+
+#[derive(CustomDebug)]
+pub struct Label {
+    width: usize,
+    #[debug = "{:>widht$}"]
+    text: &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,68 @@
+// Every type parameter that reaches a field needs a Debug bound, not only the
+// first generic argument of the field's type.
+//
+// The bound inference walks all generic arguments, so HashMap<K, V> bounds
+// both K and V and Result<T, E> bounds both T and E, and it looks through
+// tuples, arrays, slices, references and trait objects such as
+// Box<dyn Trait<T>>. A single field can therefore contribute several
+// predicates to the generated where-clause:
+//
+//     impl<K, V, T, E> Debug for Registry<K, V, T, E>
+//     where
+//         K: Debug,
+//         V: Debug,
+//         T: Debug,
+//         E: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+pub trait Source<T>: Debug {}
+
+#[derive(CustomDebug)]
+pub struct Registry<K, V, T, E, A, B, S> {
+    entries: HashMap<K, V>,
+    last: Result<T, E>,
+    pair: (A, Vec<B>),
+    source: Box<dyn Source<S>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Borrowed<'a, T, U> {
+    items: &'a [T],
+    grid: [[U; 2]; 2],
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    #[derive(Debug)]
+    struct Sensor;
+
+    impl Source<u8> for Sensor {}
+
+    assert_debug::<Registry<String, u8, (), String, char, bool, u8>>();
+    assert_debug::<Borrowed<'static, u8, i64>>();
+
+    let borrowed = Borrowed {
+        items: &[1, 2],
+        grid: [[1, 2], [3, 4]],
+    };
+    assert_eq!(
+        format!("{:?}", borrowed),
+        "Borrowed { items: [1, 2], grid: [[1, 2], [3, 4]] }",
+    );
+
+    let registry = Registry {
+        entries: HashMap::from([(1, "one")]),
+        last: Ok::<_, ()>(()),
+        pair: ('a', vec![true]),
+        source: Box::new(Sensor) as Box<dyn Source<u8>>,
+    };
+    assert_eq!(
+        format!("{:?}", registry),
+        r#"Registry { entries: {1: "one"}, last: Ok(()), pair: ('a', [true]), source: Sensor }"#,
+    );
+}
@@ -0,0 +1,73 @@
+// Associated types come in more shapes than `T::Value`.
+//
+// The bound inference emits the predicate exactly as the field spells the
+// associated type: `<T as Trait>::Value` for a qualified path, including one
+// whose self type is itself an associated type like `<C::Next as Trait>`, and
+// the inner `T::Value` for an associated type nested inside another generic
+// type like Vec<T::Value>.
+//
+//     impl<T: Trait, C: Chain> Debug for Field<T, C>
+//     where
+//         <T as Trait>::Value: Debug,
+//         <C::Next as Trait>::Value: Debug,
+//         T::Value: Debug,
+//     {...}
+//
+// PhantomData is recognized under the paths the standard library exposes it
+// at, such as core::marker::PhantomData, but a different type that happens to
+// be called PhantomData is treated like any other type.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+pub trait Chain {
+    type Next: Trait;
+}
+
+mod custom {
+    #[derive(Debug)]
+    pub struct PhantomData<T>(pub T);
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait, C: Chain, P, Q> {
+    qualified: <T as Trait>::Value,
+    chained: <C::Next as Trait>::Value,
+    nested: Vec<Option<T::Value>>,
+    marker: core::marker::PhantomData<P>,
+    custom: custom::PhantomData<Q>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    impl Chain for Id {
+        type Next = Id;
+    }
+
+    struct NotDebug;
+
+    assert_debug::<Field<Id, Id, NotDebug, u8>>();
+
+    let field = Field::<Id, Id, (), &str> {
+        qualified: 1,
+        chained: 2,
+        nested: vec![Some(3)],
+        marker: core::marker::PhantomData,
+        custom: custom::PhantomData("q"),
+    };
+    assert_eq!(
+        format!("{:?}", field),
+        r#"Field { qualified: 1, chained: 2, nested: [Some(3)], marker: PhantomData<()>, custom: PhantomData("q") }"#,
+    );
+}
@@ -0,0 +1,65 @@
+// The `debug(bound = "...")` string is parsed as a comma-separated list of
+// where-predicates, so a comma inside the generic arguments of a trait or
+// inside the arguments of an Fn bound does not split a predicate in two.
+//
+// The attribute is also accepted on an individual field, where it replaces
+// only the bounds inferred from that field's type. Here the closure type F is
+// not Debug, so the inferred `F: Debug` is replaced with the Fn bound that
+// Callback<F> needs, while `U: Debug` is still inferred from the other field.
+//
+//     impl<F, U> Debug for Handler<F, U>
+//     where
+//         for<'a> F: Fn(&'a u8, u8) -> u8,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+pub trait Pair<A, B> {
+    type Out;
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: Pair<u8, u16>, <T as Pair<u8, u16>>::Out: Debug")]
+pub struct Wrapper<T: Pair<u8, u16>> {
+    out: <T as Pair<u8, u16>>::Out,
+}
+
+pub struct Callback<F>(F);
+
+impl<F> Debug for Callback<F>
+where
+    for<'a> F: Fn(&'a u8, u8) -> u8,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Callback({})", (self.0)(&1, 2))
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Handler<F, U> {
+    #[debug(bound = "for<'a> F: Fn(&'a u8, u8) -> u8")]
+    callback: Callback<F>,
+    normal: U,
+}
+
+fn main() {
+    struct Id;
+
+    impl Pair<u8, u16> for Id {
+        type Out = &'static str;
+    }
+
+    let wrapper = Wrapper::<Id> { out: "out" };
+    assert_eq!(format!("{:?}", wrapper), r#"Wrapper { out: "out" }"#);
+
+    let handler = Handler {
+        callback: Callback(|a: &u8, b: u8| a + b),
+        normal: 'u',
+    };
+    assert_eq!(
+        format!("{:?}", handler),
+        "Handler { callback: Callback(3), normal: 'u' }",
+    );
+}
@@ -0,0 +1,13 @@
+// A `debug(bound = "...")` string that is not a valid list of where-predicates
+// is reported as a compile error pointing at the string literal, rather than
+// making the macro panic.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T Debug")]
+pub struct Wrapper<T> {
+    field: T,
+}
+
+fn main() {}
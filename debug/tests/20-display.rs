@@ -0,0 +1,66 @@
+// The same crate also provides a CustomDisplay derive, driven by a
+// `#[display("...")]` template on the struct, or on each variant of an enum.
+// Placeholders in the template name fields, `{name}` for named fields and
+// `{0}` for tuple fields, and keep their format spec like `{id:>4}`.
+//
+// Unit structs and unit variants without a template display as their name.
+//
+// Type parameters are bounded by Display using the same inference as
+// CustomDebug, but only for the fields the template mentions:
+//
+//     impl<T, U> Display for User<T, U>
+//     where
+//         T: Display,
+//     {...}
+
+use derive_debug::CustomDisplay;
+use std::fmt::Display;
+
+#[derive(CustomDisplay)]
+#[display("{name} ({id:>4})")]
+pub struct User<T, U> {
+    id: u32,
+    name: T,
+    secret: U,
+}
+
+#[derive(CustomDisplay)]
+#[display("#{0}")]
+pub struct Id(u32);
+
+#[derive(CustomDisplay)]
+pub struct Anonymous;
+
+#[derive(CustomDisplay)]
+pub enum Shape {
+    #[display("circle of radius {radius}")]
+    Circle { radius: f64, center: (f64, f64) },
+    #[display("{0}x{1} rectangle")]
+    Rectangle(u32, u32),
+    Empty,
+}
+
+fn assert_display<F: Display>() {}
+
+fn main() {
+    struct NotDisplay;
+
+    assert_display::<User<&str, NotDisplay>>();
+
+    let user = User {
+        id: 7,
+        name: "Ferris",
+        secret: NotDisplay,
+    };
+    assert_eq!(user.to_string(), "Ferris (   7)");
+    assert_eq!(Id(3).to_string(), "#3");
+    assert_eq!(Anonymous.to_string(), "Anonymous");
+
+    let circle = Shape::Circle {
+        radius: 1.5,
+        center: (0.0, 0.0),
+    };
+    assert_eq!(circle.to_string(), "circle of radius 1.5");
+    assert_eq!(Shape::Rectangle(2, 3).to_string(), "2x3 rectangle");
+    assert_eq!(Shape::Empty.to_string(), "Empty");
+}
@@ -0,0 +1,14 @@
+// A display template placeholder that names no field of the struct or variant
+// is reported during macro expansion, pointing into the template where the
+// compiler allows it.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} ({uid})")]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+fn main() {}
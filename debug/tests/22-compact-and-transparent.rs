@@ -0,0 +1,55 @@
+// Two struct-level attributes change the shape of the output as a whole.
+//
+// `#[debug(compact)]` keeps the single-line form even when the value is
+// printed with `{:#?}`, for small structs that read better on one line inside
+// an otherwise pretty-printed tree.
+//
+// `#[debug(transparent)]` on a newtype forwards directly to the Debug impl of
+// its only field, so a wrapper like UserId(u64) prints as just `42`. Field
+// attributes such as a custom format still apply to that field.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Mask {
+    #[debug = "0b{:08b}"]
+    bits: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct User {
+    id: UserId,
+    mask: Mask,
+    home: Point,
+}
+
+fn main() {
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(format!("{:?}", point), "Point { x: 1, y: 2 }");
+    assert_eq!(format!("{:#?}", point), "Point { x: 1, y: 2 }");
+
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:?}", Mask { bits: 5 }), "0b00000101");
+
+    let user = User {
+        id: UserId(42),
+        mask: Mask { bits: 5 },
+        home: point,
+    };
+    assert_eq!(
+        format!("{:#?}", user),
+        "User {\n    id: 42,\n    mask: 0b00000101,\n    home: Point { x: 1, y: 2 },\n}",
+    );
+}
@@ -0,0 +1,85 @@
+// Two field attributes change where and under what name a field appears.
+//
+// `#[debug(rename = "id")]` prints the field under a different label.
+//
+// `#[debug(flatten)]` inlines the fields of a nested struct into the parent's
+// output as if they had been declared on the parent. The nested struct must
+// derive CustomDebug with `#[debug(flattenable)]`, which emits a hidden helper
+// that writes its fields into an existing DebugStruct, which is what the
+// parent calls for the flattened field. If the flattened struct skips some of
+// its fields, the parent's output ends in `..` too.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(flattenable)]
+pub struct Audit {
+    created_by: &'static str,
+    #[debug(skip)]
+    revision: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(flattenable)]
+pub struct Position<T> {
+    line: T,
+    column: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Document {
+    #[debug(rename = "id")]
+    document_id: u64,
+    #[debug(flatten)]
+    position: Position<u32>,
+    title: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Record {
+    #[debug(flatten)]
+    audit: Audit,
+    #[debug(rename = "kind")]
+    record_kind: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Moved {
+        #[debug(flatten)]
+        to: Position<i64>,
+    },
+}
+
+fn main() {
+    let document = Document {
+        document_id: 7,
+        position: Position { line: 3, column: 14 },
+        title: "notes",
+    };
+    assert_eq!(
+        format!("{:?}", document),
+        r#"Document { id: 7, line: 3, column: 14, title: "notes" }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", document),
+        "Document {\n    id: 7,\n    line: 3,\n    column: 14,\n    title: \"notes\",\n}",
+    );
+
+    let record = Record {
+        audit: Audit {
+            created_by: "ferris",
+            revision: 2,
+        },
+        record_kind: "note",
+    };
+    assert_eq!(
+        format!("{:?}", record),
+        r#"Record { created_by: "ferris", kind: "note", .. }"#,
+    );
+
+    let event = Event::Moved {
+        to: Position { line: -1, column: 0 },
+    };
+    assert_eq!(format!("{:?}", event), "Moved { line: -1, column: 0 }");
+}
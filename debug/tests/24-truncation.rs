@@ -0,0 +1,56 @@
+// Large collections and long strings can flood the output, so fields accept
+// limits on how much of them is printed.
+//
+// `#[debug(limit = N)]` applies to any field whose shared reference can be
+// iterated, printing the first N items as a list followed by a note of how
+// many were left out. `#[debug(max_len = N)]` applies to any field that
+// derefs to str, printing the first N characters of it.
+//
+// A struct-level `#[debug(limit = N)]` is the default limit for every field
+// whose type is spelled as a standard collection, an array or a slice. A
+// field-level limit overrides it.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(limit = 3)]
+    payload: Vec<u8>,
+    #[debug(max_len = 5)]
+    summary: String,
+    #[debug(max_len = 5)]
+    label: &'static str,
+}
+
+#[derive(CustomDebug)]
+#[debug(limit = 2)]
+pub struct Batch {
+    ids: Vec<u32>,
+    checksum: [u8; 4],
+    #[debug(limit = 4)]
+    names: &'static [&'static str],
+    count: usize,
+}
+
+fn main() {
+    let packet = Packet {
+        payload: (0..100).collect(),
+        summary: "hello world".to_owned(),
+        label: "short",
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        r#"Packet { payload: [0, 1, 2, ... (97 more)], summary: "hello"... (6 more), label: "short" }"#,
+    );
+
+    let batch = Batch {
+        ids: vec![7, 8],
+        checksum: [1, 2, 3, 4],
+        names: &["a", "b", "c", "d", "e"],
+        count: 2,
+    };
+    assert_eq!(
+        format!("{:?}", batch),
+        r#"Batch { ids: [7, 8], checksum: [1, 2, ... (2 more)], names: ["a", "b", "c", "d", ... (1 more)], count: 2 }"#,
+    );
+}
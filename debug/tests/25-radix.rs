@@ -0,0 +1,46 @@
+// Integer fields often read better in another base. The `#[debug(hex)]`,
+// `#[debug(binary)]` and `#[debug(octal)]` shorthands print them with `{:#x}`,
+// `{:#b}` and `{:#o}` respectively.
+//
+// Unlike a `#[debug = "..."]` format string, which applies to the field as a
+// whole, the shorthands also apply element-wise to integers inside Option,
+// Vec, arrays, slices and references, in any combination.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Registers {
+    #[debug(hex)]
+    address: u32,
+    #[debug(binary)]
+    flags: u8,
+    #[debug(octal)]
+    mode: u16,
+    #[debug(hex)]
+    status: Option<u32>,
+    #[debug(hex)]
+    stack: Vec<Option<i64>>,
+    #[debug(hex)]
+    key: [u8; 4],
+    #[debug(binary)]
+    mask: &'static [u8],
+    plain: u8,
+}
+
+fn main() {
+    let registers = Registers {
+        address: 0xdead_beef,
+        flags: 0b1010,
+        mode: 0o755,
+        status: Some(42),
+        stack: vec![Some(255), None],
+        key: [1, 2, 0xab, 0xff],
+        mask: &[3],
+        plain: 9,
+    };
+    assert_eq!(
+        format!("{:?}", registers),
+        "Registers { address: 0xdeadbeef, flags: 0b1010, mode: 0o755, status: Some(0x2a), \
+         stack: [Some(0xff), None], key: [0x1, 0x2, 0xab, 0xff], mask: [0b11], plain: 9 }",
+    );
+}
@@ -0,0 +1,82 @@
+// The generated impls only refer to `::core`, never to `std`, so the derives
+// work in `#![no_std]` crates.
+//
+// This test is a no_std crate. The standard library is linked under a
+// different name only so that the test binary has a runtime to run in, which
+// means any `std::` path emitted by the macro fails to resolve. Fields of
+// types from `alloc`, like the Vec below, are spelled by the crate the way it
+// imports them, and the derive follows that spelling.
+
+#![no_std]
+
+extern crate std as host;
+
+use core::fmt::{self, Write};
+use derive_debug::{CustomDebug, CustomDisplay};
+use host::vec::Vec;
+
+#[derive(CustomDebug, CustomDisplay)]
+#[display("{name}: {value}")]
+pub struct Reading<T> {
+    name: &'static str,
+    #[debug(hex)]
+    raw: Vec<u16>,
+    #[debug(limit = 2)]
+    history: [T; 3],
+    #[debug(max_len = 3)]
+    unit: &'static str,
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Id(u8);
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub enum State {
+    Idle,
+    Busy { id: Id },
+}
+
+// formats into a fixed buffer, without allocating
+struct Buffer {
+    bytes: [u8; 256],
+    len: usize,
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn check(args: fmt::Arguments, expected: &str) {
+    let mut buffer = Buffer {
+        bytes: [0; 256],
+        len: 0,
+    };
+    buffer.write_fmt(args).unwrap();
+    assert_eq!(core::str::from_utf8(&buffer.bytes[..buffer.len]).unwrap(), expected);
+}
+
+fn main() {
+    let reading = Reading {
+        name: "temp",
+        raw: Vec::from([0x1f, 0xa0]),
+        history: [20, 21, 22],
+        unit: "celsius",
+        value: 21,
+    };
+    check(
+        format_args!("{:?}", reading),
+        r#"Reading { name: "temp", raw: [0x1f, 0xa0], history: [20, 21, ... (1 more)], unit: "cel"... (4 more), value: 21 }"#,
+    );
+    check(format_args!("{}", reading), "temp: 21");
+
+    check(format_args!("{:#?}", State::Busy { id: Id(3) }), "Busy { id: 3 }");
+    check(format_args!("{:?}", State::Idle), "Idle");
+}
@@ -0,0 +1,66 @@
+// Bound inference looks through every shape of type a field can have, not
+// only paths. Arrays, slices, references and tuples are bounded by their
+// element types, and lifetimes and const generic parameters pass through to
+// the impl untouched:
+//
+//     impl<'a, T, const N: usize> Debug for Buf<'a, T, N>
+//     where
+//         T: Debug,
+//     {...}
+//
+// Raw pointers and fn pointers are Debug no matter what they point to, so
+// they add no bound on the types they mention.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Buf<'a, T, const N: usize> {
+    data: [T; N],
+    r: &'a [T],
+    pair: (T, [u8; N]),
+}
+
+#[derive(CustomDebug)]
+pub struct Handles<P, F> {
+    ptr: *const P,
+    callback: fn(F) -> F,
+    #[debug(skip)]
+    owned: Option<Box<dyn Fn(&P) -> F>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Nested<T, const N: usize> {
+    grid: Option<[[T; N]; 2]>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    assert_debug::<Buf<u8, 2>>();
+    assert_debug::<Handles<NotDebug, NotDebug>>();
+    assert_debug::<Nested<u8, 3>>();
+
+    let values = [7, 8];
+    let buf = Buf {
+        data: [1, 2],
+        r: &values,
+        pair: (3, [4, 5]),
+    };
+    assert_eq!(
+        format!("{:?}", buf),
+        "Buf { data: [1, 2], r: [7, 8], pair: (3, [4, 5]) }",
+    );
+
+    let nested = Nested { grid: Some([[1], [2]]) };
+    assert_eq!(format!("{:?}", nested), "Nested { grid: Some([[1], [2]]) }");
+
+    let handles = Handles::<NotDebug, NotDebug> {
+        ptr: std::ptr::null(),
+        callback: |f| f,
+        owned: None,
+    };
+    assert!(format!("{:?}", handles).starts_with("Handles { ptr: 0x0, callback: 0x"));
+}
@@ -0,0 +1,26 @@
+// Only structs with named fields can be flattened into another struct, and
+// only when they opt in with `#[debug(flattenable)]`.
+//
+// Asking for it on a tuple struct or an enum is reported at the type, and
+// flattening a field whose type did not opt in is reported at that field's
+// type.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(flattenable)]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Position {
+    line: u32,
+    column: u32,
+}
+
+#[derive(CustomDebug)]
+pub struct Document {
+    #[debug(flatten)]
+    position: Position,
+}
+
+fn main() {}
@@ -9,4 +9,24 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.compile_fail("tests/10-union.rs");
+    t.pass("tests/11-tuple-and-unit-struct.rs");
+    t.pass("tests/12-skip-and-redact.rs");
+    t.pass("tests/13-with-function.rs");
+    t.pass("tests/14-format-fields.rs");
+    t.compile_fail("tests/15-unknown-format-field.rs");
+    t.pass("tests/16-bound-every-argument.rs");
+    t.pass("tests/17-qualified-paths.rs");
+    t.pass("tests/18-bound-parser.rs");
+    t.compile_fail("tests/19-invalid-bound.rs");
+    t.pass("tests/20-display.rs");
+    t.compile_fail("tests/21-display-unknown-field.rs");
+    t.pass("tests/22-compact-and-transparent.rs");
+    t.pass("tests/23-rename-and-flatten.rs");
+    t.pass("tests/24-truncation.rs");
+    t.pass("tests/25-radix.rs");
+    t.pass("tests/26-no-std.rs");
+    t.pass("tests/27-const-generics.rs");
+    t.compile_fail("tests/28-flatten-unsupported.rs");
 }